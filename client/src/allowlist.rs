@@ -0,0 +1,160 @@
+//! Merkle allowlist builder for invite-only streams.
+//!
+//! Hashes with the program's own `allowlist` module, so roots and proofs match
+//! what `join_stream` verifies. An odd node at the end of a level is carried up
+//! unchanged. Pass [`AllowlistTree::root`] to `set_allowlist` and each viewer's
+//! [`AllowlistTree::proof`] to `join_stream`.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use cyphercast::allowlist::{hash_pair, leaf};
+
+/// Full allowlist tree over `(viewer, stake_cap)` entries.
+pub struct AllowlistTree {
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl AllowlistTree {
+    /// Build the tree over `(viewer, stake_cap)` entries, in the given order.
+    pub fn new(entries: &[(Pubkey, u64)]) -> Self {
+        let mut levels = vec![entries
+            .iter()
+            .map(|(viewer, cap)| leaf(viewer, *cap))
+            .collect::<Vec<_>>()];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// Root to store with `set_allowlist`; all zeroes for an empty list.
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Proof for the entry at `index`, or `None` if out of range.
+    pub fn proof(&self, mut index: usize) -> Option<Vec<[u8; 32]>> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+/// Parse one `viewer[,stake_cap]` line; a missing cap means no cap.
+pub fn parse_entry(line: &str) -> Result<(Pubkey, u64), String> {
+    let mut fields = line.split(',').map(str::trim);
+    let viewer = fields.next().unwrap_or_default();
+    let viewer = Pubkey::from_str(viewer).map_err(|_| format!("invalid viewer `{viewer}`"))?;
+    let stake_cap = match fields.next() {
+        Some(cap) => cap
+            .parse()
+            .map_err(|_| format!("invalid stake cap `{cap}`"))?,
+        None => 0,
+    };
+    if fields.next().is_some() {
+        return Err(format!("too many fields in `{line}`"));
+    }
+    Ok((viewer, stake_cap))
+}
+
+/// Lowercase hex encoding of a node.
+pub fn to_hex(node: &[u8; 32]) -> String {
+    node.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cyphercast::allowlist::{verify, MAX_PROOF_LEN};
+
+    fn entries(n: usize) -> Vec<(Pubkey, u64)> {
+        (0..n)
+            .map(|i| (Pubkey::new_unique(), (i as u64 % 3) * 1_000_000))
+            .collect()
+    }
+
+    #[test]
+    fn every_proof_verifies_on_chain() {
+        for n in (1..=17).chain([100, 1000]) {
+            let list = entries(n);
+            let tree = AllowlistTree::new(&list);
+            let root = tree.root();
+            let depth = (n as f64).log2().ceil() as usize;
+
+            for (index, (viewer, cap)) in list.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert!(proof.len() <= depth.min(MAX_PROOF_LEN));
+                assert!(verify(&root, leaf(viewer, *cap), &proof), "n = {n}");
+            }
+        }
+    }
+
+    #[test]
+    fn matches_hand_built_tree_with_carried_up_leaf() {
+        let list = entries(3);
+        let [a, b, c] = [0, 1, 2].map(|i| leaf(&list[i].0, list[i].1));
+        let tree = AllowlistTree::new(&list);
+
+        assert_eq!(tree.root(), hash_pair(&hash_pair(&a, &b), &c));
+        assert_eq!(tree.proof(2).unwrap(), vec![hash_pair(&a, &b)]);
+    }
+
+    #[test]
+    fn rejects_proof_for_other_cap_viewer_or_index() {
+        let list = entries(8);
+        let tree = AllowlistTree::new(&list);
+        let (viewer, cap) = list[5];
+        let proof = tree.proof(5).unwrap();
+
+        assert!(!verify(&tree.root(), leaf(&viewer, cap + 1), &proof));
+        assert!(!verify(
+            &tree.root(),
+            leaf(&Pubkey::new_unique(), cap),
+            &proof
+        ));
+        assert!(!verify(
+            &tree.root(),
+            leaf(&viewer, cap),
+            &tree.proof(4).unwrap()
+        ));
+    }
+
+    #[test]
+    fn empty_list_and_out_of_range_index() {
+        let empty = AllowlistTree::new(&[]);
+        assert_eq!(empty.root(), [0; 32]);
+        assert!(empty.proof(0).is_none());
+        assert!(AllowlistTree::new(&entries(4)).proof(4).is_none());
+    }
+
+    #[test]
+    fn parses_entries() {
+        let viewer = Pubkey::new_unique();
+        assert_eq!(parse_entry(&viewer.to_string()), Ok((viewer, 0)));
+        assert_eq!(parse_entry(&format!("{viewer}, 500")), Ok((viewer, 500)));
+        assert!(parse_entry("not-a-key").is_err());
+        assert!(parse_entry(&format!("{viewer},-1")).is_err());
+        assert!(parse_entry(&format!("{viewer},1,2")).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+//! Build an invite-only stream's allowlist.
+//!
+//! Usage: `cyphercast-allowlist <entries-file>`
+//!
+//! The file holds one `viewer[,stake_cap]` entry per line; blank lines and lines
+//! starting with `#` are skipped. Prints JSON with the root for `set_allowlist`
+//! and every viewer's proof for `join_stream`, all hex encoded.
+
+use std::{env, fs, process};
+
+use cyphercast_client::allowlist::{parse_entry, to_hex, AllowlistTree};
+
+fn main() {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("usage: cyphercast-allowlist <entries-file>");
+        process::exit(2);
+    };
+    let input = fs::read_to_string(&path).unwrap_or_else(|err| {
+        eprintln!("cannot read {path}: {err}");
+        process::exit(1);
+    });
+
+    let mut entries = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_entry(line) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                eprintln!("{path}:{}: {err}", number + 1);
+                process::exit(1);
+            }
+        }
+    }
+
+    let tree = AllowlistTree::new(&entries);
+    let proofs: Vec<String> = entries
+        .iter()
+        .enumerate()
+        .map(|(index, (viewer, stake_cap))| {
+            let proof: Vec<String> = tree
+                .proof(index)
+                .unwrap_or_default()
+                .iter()
+                .map(|node| format!("\"{}\"", to_hex(node)))
+                .collect();
+            format!(
+                "    {{ \"viewer\": \"{viewer}\", \"stakeCap\": {stake_cap}, \"proof\": [{}] }}",
+                proof.join(", ")
+            )
+        })
+        .collect();
+
+    println!("{{");
+    println!("  \"root\": \"{}\",", to_hex(&tree.root()));
+    println!("  \"entries\": [");
+    println!("{}", proofs.join(",\n"));
+    println!("  ]");
+    println!("}}");
+}
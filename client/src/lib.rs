@@ -0,0 +1,3 @@
+//! Off-chain helpers for CypherCast clients.
+
+pub mod allowlist;
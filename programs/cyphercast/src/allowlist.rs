@@ -0,0 +1,109 @@
+//! Merkle allowlist for invite-only streams.
+//!
+//! Leaves commit to `(viewer, stake_cap)`; interior nodes hash their two
+//! children in sorted order, so a proof is just the list of siblings from the
+//! leaf up to the root. Leaves and nodes use distinct prefixes so a leaf can
+//! never be passed off as an interior node. A tree of 100k entries needs 17
+//! proof hashes, well within the compute budget.
+//!
+//! Trees and proofs are built off-chain by `AllowlistTree` in the Rust client
+//! (`client/`), which reuses these hashes.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+
+/// Longest proof accepted on-chain (trees of up to 2^24 entries).
+pub const MAX_PROOF_LEN: usize = 24;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Leaf for `viewer` with an optional cumulative stake cap (0 = no cap).
+pub fn leaf(viewer: &Pubkey, stake_cap: u64) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, viewer.as_ref(), &stake_cap.to_le_bytes()]).to_bytes()
+}
+
+/// Parent of two nodes, independent of their order.
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Whether `proof` links `leaf` to `root`.
+pub fn verify(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof.len() <= MAX_PROOF_LEN
+        && proof
+            .iter()
+            .fold(leaf, |node, sibling| hash_pair(&node, sibling))
+            == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewers(n: usize) -> Vec<Pubkey> {
+        (0..n).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn single_leaf_is_its_own_root() {
+        let viewer = Pubkey::new_unique();
+        let root = leaf(&viewer, 0);
+        assert!(verify(&root, leaf(&viewer, 0), &[]));
+        assert!(!verify(&root, leaf(&viewer, 1), &[]));
+    }
+
+    #[test]
+    fn three_leaf_tree_round_trip() {
+        let v = viewers(3);
+        let leaves: Vec<_> = v.iter().map(|viewer| leaf(viewer, 100)).collect();
+        // The odd leaf is carried up unchanged
+        let left = hash_pair(&leaves[0], &leaves[1]);
+        let root = hash_pair(&left, &leaves[2]);
+
+        assert!(verify(&root, leaves[0], &[leaves[1], leaves[2]]));
+        assert!(verify(&root, leaves[1], &[leaves[0], leaves[2]]));
+        assert!(verify(&root, leaves[2], &[left]));
+    }
+
+    #[test]
+    fn rejects_wrong_cap_viewer_or_proof() {
+        let v = viewers(2);
+        let a = leaf(&v[0], 50);
+        let b = leaf(&v[1], 0);
+        let root = hash_pair(&a, &b);
+
+        assert!(verify(&root, a, &[b]));
+        assert!(!verify(&root, leaf(&v[0], 51), &[b]));
+        assert!(!verify(&root, leaf(&Pubkey::new_unique(), 50), &[b]));
+        assert!(!verify(&root, a, &[a]));
+        assert!(!verify(&root, a, &[]));
+    }
+
+    #[test]
+    fn pair_hash_is_order_independent() {
+        let a = leaf(&Pubkey::new_unique(), 0);
+        let b = leaf(&Pubkey::new_unique(), 0);
+        assert_eq!(hash_pair(&a, &b), hash_pair(&b, &a));
+        assert_ne!(hash_pair(&a, &b), a);
+    }
+
+    #[test]
+    fn rejects_overlong_proof() {
+        let viewer = Pubkey::new_unique();
+        let sibling = leaf(&Pubkey::new_unique(), 0);
+        let proof = vec![sibling; MAX_PROOF_LEN + 1];
+        let root = proof
+            .iter()
+            .fold(leaf(&viewer, 0), |node, sibling| hash_pair(&node, sibling));
+        assert!(!verify(&root, leaf(&viewer, 0), &proof));
+        assert!(verify(
+            &proof[..MAX_PROOF_LEN]
+                .iter()
+                .fold(leaf(&viewer, 0), |node, sibling| hash_pair(&node, sibling)),
+            leaf(&viewer, 0),
+            &proof[..MAX_PROOF_LEN]
+        ));
+    }
+}
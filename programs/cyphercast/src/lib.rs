@@ -1,6 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+
+pub mod allowlist;
+pub mod lmsr;
+pub mod metaplex;
+pub mod validation;
 
 declare_id!("5a3LkJ73xWyYd7M9jqZtbGY1p9gyJfzSXvHEJdY9ohTF");
 
@@ -11,6 +16,19 @@ pub mod cyphercast {
     /// Maximum number of prediction choices supported by the program.
     pub const MAX_CHOICES: u8 = 10;
 
+    /// Maximum number of positions a ranked market can ask viewers to order.
+    pub const MAX_RANK_DEPTH: usize = 5;
+
+    /// Maximum number of legs in a parlay.
+    pub const MAX_PARLAY_LEGS: usize = 4;
+
+    /// How long after a leg stream locks it may stay unresolved before its parlays are voided.
+    pub const PARLAY_SETTLEMENT_GRACE_SECS: i64 = 7 * 24 * 60 * 60;
+
+    /// How long after a late cancellation refunds keep their share of a slashed bond before
+    /// `distribute_slashed_bond` may sweep what is left.
+    pub const BOND_CLAIM_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+
     /// Size of the discriminator added by Anchor to all accounts
     pub const DISCRIMINATOR: usize = 8;
 
@@ -19,9 +37,9 @@ pub mod cyphercast {
         let vault = &mut ctx.accounts.vault;
         let stream = &ctx.accounts.stream;
 
-        // Only the stream creator can initialize the vault
+        // Only the stream owner can initialize the vault
         require!(
-            stream.creator == *ctx.accounts.creator.key,
+            stream.owner == *ctx.accounts.creator.key,
             CypherCastError::Unauthorized
         );
 
@@ -40,9 +58,9 @@ pub mod cyphercast {
         Ok(())
     }
 
+    /// Create a Draft stream under the next ID from the creator's profile.
     pub fn create_stream(
         ctx: Context<CreateStream>,
-        stream_id: u64,
         title: String,
         start_time: i64,
         lock_offset_secs: i64,
@@ -54,6 +72,21 @@ pub mod cyphercast {
         require!(title.as_bytes().len() <= 200, CypherCastError::TitleTooLong);
         require!(precision <= 9, CypherCastError::InvalidConfig);
         require!(tip_bps <= 10_000, CypherCastError::InvalidConfig);
+        validation::validate_schedule(
+            Clock::get()?.unix_timestamp,
+            start_time,
+            lock_offset_secs,
+            grace_period_secs,
+        )?;
+
+        // The stream PDA was derived from `next_stream_id`; claim it
+        let profile = &mut ctx.accounts.creator_profile;
+        let stream_id = profile.next_stream_id;
+        profile.next_stream_id = stream_id.checked_add(1).ok_or(CypherCastError::Overflow)?;
+        profile.total_streams = profile
+            .total_streams
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
 
         stream.creator = *ctx.accounts.creator.key;
         stream.stream_id = stream_id;
@@ -77,23 +110,172 @@ pub mod cyphercast {
         stream.tip_amount = 0;
         stream.resolved_at = 0;
         stream.canceled_at = 0;
+        // Payout mode (parimutuel until configured otherwise in Draft)
+        stream.market_mode = MarketMode::Parimutuel;
+        stream.odds_bps = [0; 11];
+        stream.house_reserve = 0;
+        stream.liability_by_choice = [0; 11];
+        stream.house_settled = false;
+        stream.lmsr_b = 0;
+        stream.lmsr_outcomes = 0;
+        stream.shares_by_choice = [0; 11];
+        stream.refund_liability = 0;
+        stream.scalar_min = 0;
+        stream.scalar_max = 0;
+        stream.scalar_scoring = ScalarScoring::InverseDistance;
+        stream.scalar_result = 0;
+        stream.prediction_count = 0;
+        stream.tallied_count = 0;
+        stream.total_weight = 0;
+        stream.winner_weights = [0; 11];
+        stream.is_void = false;
+        stream.rank_depth = 0;
+        stream.rank_points = RankPoints::default();
+        stream.final_ranking = [0; MAX_RANK_DEPTH];
+        stream.seeded_by_choice = [0; 11];
+        stream.bonus_pool = 0;
+        stream.sponsorship_count = 0;
+        stream.free_play_split = FreePlaySplit::Equal;
+        stream.gate = StreamGate::None;
+        stream.entry_fee = 0;
+        stream.entry_fee_split = EntryFeeSplit::default();
+        stream.entry_fees_collected = 0;
+        stream.entry_fee_prize = 0;
+        stream.entry_fees_distributed = false;
+        stream.allowlist_root = [0; 32];
+        stream.min_stake = 0;
+        stream.max_stake_per_viewer = 0;
+        stream.max_share_bps_of_choice = 0;
+        stream.share_limit_min_pool = 0;
+        stream.min_participants = 0;
+        stream.min_total_stake = 0;
+        stream.require_two_sided = false;
+        stream.lock_finalized = false;
+        stream.locked_at = 0;
+        stream.is_paused = false;
+        stream.max_lock_extension_secs = 0;
+        stream.withdrawal_window_secs = 0;
+        stream.lock_extension_secs = 0;
+        stream.rescheduled_at = 0;
+        stream.withdrawal_deadline = 0;
+        stream.min_observation_secs = 0;
+        stream.bond_amount = 0;
+        stream.bond_slash_target = BondSlashTarget::Predictors;
+        stream.arbiter = Pubkey::default();
+        stream.bond_posted = false;
+        stream.bond_slashed = false;
+        stream.bond_released = false;
+        stream.bond_poster = Pubkey::default();
+        stream.bond_distributed = 0;
+        stream.refunded_stake = 0;
+        stream.owner = *ctx.accounts.creator.key;
+        stream.has_metadata = false;
         stream.bump = ctx.bumps.stream;
 
         msg!("Stream created: {} by {}", title, stream.creator);
         Ok(())
     }
 
-    pub fn join_stream(ctx: Context<JoinStream>, _stake_amount: u64) -> Result<()> {
+    pub fn join_stream(
+        ctx: Context<JoinStream>,
+        _stake_amount: u64,
+        allowlist: Option<AllowlistProof>,
+    ) -> Result<()> {
         let stream = &mut ctx.accounts.stream;
         let participant = &mut ctx.accounts.participant;
 
         require!(stream.is_active, CypherCastError::StreamNotActive);
         require!(stream.canceled_at == 0, CypherCastError::Canceled);
 
+        // Invite-only streams: the viewer (and their stake cap) must be in the allowlist tree
+        let stake_cap = if stream.allowlist_root != [0u8; 32] {
+            let entry = allowlist.ok_or(CypherCastError::NotAllowlisted)?;
+            let leaf = allowlist::leaf(ctx.accounts.viewer.key, entry.stake_cap);
+            require!(
+                allowlist::verify(&stream.allowlist_root, leaf, &entry.proof),
+                CypherCastError::NotAllowlisted
+            );
+            entry.stake_cap
+        } else {
+            0
+        };
+        verify_gate(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.gate_token_account.as_ref(),
+            ctx.accounts.gate_metadata.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+
+        // Premium streams: escrow the entry fee in the vault and issue an access pass
+        if stream.entry_fee > 0 {
+            let (
+                Some(access_pass),
+                Some(vault),
+                Some(viewer_token_account),
+                Some(vault_token_account),
+                Some(token_program),
+            ) = (
+                ctx.accounts.access_pass.as_mut(),
+                ctx.accounts.vault.as_ref(),
+                ctx.accounts.viewer_token_account.as_ref(),
+                ctx.accounts.vault_token_account.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            )
+            else {
+                return err!(CypherCastError::AccessPassRequired);
+            };
+            require!(
+                viewer_token_account.mint == vault.mint
+                    && vault_token_account.key() == vault.token_account,
+                CypherCastError::AccessPassRequired
+            );
+
+            let cpi_ctx = CpiContext::new(
+                token_program.to_account_info(),
+                Transfer {
+                    from: viewer_token_account.to_account_info(),
+                    to: vault_token_account.to_account_info(),
+                    authority: ctx.accounts.viewer.to_account_info(),
+                },
+            );
+            token::transfer(cpi_ctx, stream.entry_fee)?;
+
+            // House-backed modes have no bonus pool; their prize share goes to the community
+            let prize_amount = if stream.is_house_backed() {
+                0
+            } else {
+                pro_rata(stream.entry_fee, stream.entry_fee_split.prize_bps, 10_000)?
+            };
+            stream.entry_fees_collected = stream
+                .entry_fees_collected
+                .checked_add(stream.entry_fee)
+                .ok_or(CypherCastError::Overflow)?;
+            stream.entry_fee_prize = stream
+                .entry_fee_prize
+                .checked_add(prize_amount)
+                .ok_or(CypherCastError::Overflow)?;
+            stream.bonus_pool = stream
+                .bonus_pool
+                .checked_add(prize_amount)
+                .ok_or(CypherCastError::Overflow)?;
+
+            access_pass.stream = stream.key();
+            access_pass.viewer = *ctx.accounts.viewer.key;
+            access_pass.amount_paid = stream.entry_fee;
+            access_pass.prize_amount = prize_amount;
+            access_pass.purchased_at = Clock::get()?.unix_timestamp;
+            access_pass.refunded = false;
+            access_pass.bump = ctx.bumps.access_pass.unwrap_or_default();
+        }
+
         participant.stream = stream.key();
         participant.viewer = *ctx.accounts.viewer.key;
         participant.stake_amount = 0;
+        participant.stake_cap = stake_cap;
+        participant.stake_by_choice = [0; 11];
         participant.joined_at = Clock::get()?.unix_timestamp;
+        participant.allowlist_root = stream.allowlist_root;
         participant.bump = ctx.bumps.participant;
 
         msg!(
@@ -116,16 +298,52 @@ pub mod cyphercast {
         require!(stream.is_active, CypherCastError::StreamNotActive);
         require!(stream.canceled_at == 0, CypherCastError::Canceled);
         require!(stake_amount > 0, CypherCastError::InvalidStakeAmount);
+        require_access_pass(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.access_pass.as_ref(),
+        )?;
+        enforce_stake_limits(
+            stream,
+            ctx.accounts.participant.as_deref_mut(),
+            Some(choice),
+            stake_amount,
+        )?;
+        verify_gate(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.gate_token_account.as_ref(),
+            ctx.accounts.gate_metadata.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
         require!(choice <= MAX_CHOICES, CypherCastError::InvalidChoice);
-        // Auto time-based lock: reject submissions at/after cutoff
-        let now = Clock::get()?.unix_timestamp;
         require!(
-            now < stream
-                .start_time
-                .checked_add(stream.lock_offset_secs)
-                .ok_or(CypherCastError::Overflow)?,
-            CypherCastError::StreamLocked
+            matches!(
+                stream.market_mode,
+                MarketMode::Parimutuel | MarketMode::FixedOdds
+            ),
+            CypherCastError::UnsupportedMarketMode
         );
+        // Reject submissions while paused or at/after the (manual or scheduled) lock
+        let now = Clock::get()?.unix_timestamp;
+        stream.require_open_for_predictions(now)?;
+
+        // Fixed-odds streams only accept stakes the house reserve can cover if this choice wins
+        if stream.market_mode == MarketMode::FixedOdds {
+            let idx = choice as usize;
+            require!(stream.odds_bps[idx] > 0, CypherCastError::InvalidChoice);
+            let payout = stream.fixed_odds_payout(choice, stake_amount)?;
+            let liability = stream.liability_by_choice[idx]
+                .checked_add(payout)
+                .ok_or(CypherCastError::Overflow)?;
+            let capacity = stream
+                .house_reserve
+                .checked_add(stream.total_stake)
+                .and_then(|v| v.checked_add(stake_amount))
+                .ok_or(CypherCastError::Overflow)?;
+            require!(liability <= capacity, CypherCastError::ReserveExceeded);
+            stream.liability_by_choice[idx] = liability;
+        }
 
         // Transfer SPL tokens from viewer's ATA to vault ATA for prediction stake
         let cpi_ctx = CpiContext::new(
@@ -159,8 +377,17 @@ pub mod cyphercast {
         prediction.stake_amount = stake_amount;
         prediction.timestamp = Clock::get()?.unix_timestamp;
         prediction.reward_claimed = false;
+        prediction.scalar_value = 0;
+        prediction.ranking = [0; MAX_RANK_DEPTH];
+        prediction.weight = 0;
+        prediction.tallied = false;
         prediction.bump = ctx.bumps.prediction;
 
+        stream.prediction_count = stream
+            .prediction_count
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+
         msg!(
             "Prediction submitted: choice {} with stake {} tokens by {}",
             choice,
@@ -203,72 +430,72 @@ pub mod cyphercast {
         let stream = &mut ctx.accounts.stream;
 
         require!(stream.is_active, CypherCastError::StreamNotActive);
-        require!(
-            stream.creator == *ctx.accounts.creator.key,
-            CypherCastError::Unauthorized
-        );
+        require_role(
+            stream,
+            ctx.accounts.stream_roles.as_deref(),
+            ctx.accounts.authority.key,
+            StreamRole::Moderator,
+        )?;
 
         stream.end_time = Clock::get()?.unix_timestamp;
         stream.is_active = false;
 
-        msg!("Stream {} ended by creator", stream.stream_id);
+        msg!(
+            "Stream {} ended by {}",
+            stream.stream_id,
+            ctx.accounts.authority.key
+        );
         Ok(())
     }
 
     pub fn resolve_prediction(ctx: Context<ResolvePrediction>, winning_choice: u8) -> Result<()> {
         let stream = &mut ctx.accounts.stream;
 
-        // The stream must be inactive before resolving predictions.
-        require!(!stream.is_active, CypherCastError::StreamStillActive);
         // The stream cannot be resolved more than once.
         require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+        // Resolution waits for the lock and the observation window, and ends the stream.
+        stream.end_for_resolution(Clock::get()?.unix_timestamp)?;
+        // Streams with participation thresholds must pass `finalize_lock` first
+        require!(
+            stream.lock_finalized || !stream.has_participation_threshold(),
+            CypherCastError::LockNotFinalized
+        );
         // Validate the winning choice against the maximum allowed choices.
         require!(
             winning_choice <= MAX_CHOICES,
             CypherCastError::InvalidChoice
         );
-        // Only the creator of the stream can resolve the prediction.
         require!(
-            stream.creator == *ctx.accounts.creator.key,
-            CypherCastError::Unauthorized
+            stream.market_mode != MarketMode::Lmsr || winning_choice < stream.lmsr_outcomes,
+            CypherCastError::InvalidChoice
+        );
+        // Scalar and ranked markets resolve through `resolve_scalar` / `resolve_ranking`
+        require!(
+            stream.resolves_to_choice(),
+            CypherCastError::UnsupportedMarketMode
         );
+        // Only the owner or a resolver can resolve the prediction.
+        require_role(
+            stream,
+            ctx.accounts.stream_roles.as_deref(),
+            ctx.accounts.authority.key,
+            StreamRole::Resolver,
+        )?;
 
         // Compute and distribute streamer tip (once) at resolve time
-        // tip_amount = floor(vault.total_deposited * tip_percent / 100)
-        let total_pool = ctx.accounts.vault.total_deposited;
-        if stream.tip_amount == 0 && stream.tip_bps > 0 {
-            let tip_amount =
-                ((total_pool as u128).saturating_mul(stream.tip_bps as u128) / 10_000u128) as u64;
-
-            if tip_amount > 0 {
-                let stream_key = stream.key();
-                let vault_bump = ctx.accounts.vault.bump;
-                let signer_seeds: &[&[&[u8]]] = &[&[b"vault", stream_key.as_ref(), &[vault_bump]]];
-
-                let cpi_ctx = CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.vault_token_account.to_account_info(),
-                        to: ctx.accounts.creator_token_account.to_account_info(),
-                        authority: ctx.accounts.vault.to_account_info(),
-                    },
-                    signer_seeds,
-                );
-                token::transfer(cpi_ctx, tip_amount)?;
-
-                // track release
-                ctx.accounts.vault.total_released = ctx
-                    .accounts
-                    .vault
-                    .total_released
-                    .checked_add(tip_amount)
-                    .ok_or(CypherCastError::Overflow)?;
-                stream.tip_amount = tip_amount;
-            }
-        }
+        pay_streamer_tip(
+            stream,
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.creator_token_account,
+        )?;
 
         stream.is_resolved = true;
+        record_resolution(stream, &mut ctx.accounts.creator_profile)?;
         stream.winning_choice = winning_choice;
+        stream.winner_weights = [0; 11];
+        stream.winner_weights[winning_choice as usize] = 1;
         stream.resolved_at = Clock::get()?.unix_timestamp;
 
         msg!(
@@ -294,11 +521,7 @@ pub mod cyphercast {
         // Ensure the stream has been resolved and not canceled.
         require!(stream.is_resolved, CypherCastError::NotResolved);
         require!(stream.canceled_at == 0, CypherCastError::Canceled);
-        // Only predictions that match the winning choice can claim rewards.
-        require!(
-            prediction.choice == stream.winning_choice,
-            CypherCastError::NotWinner
-        );
+        require!(!stream.is_void, CypherCastError::StreamVoided);
         // Prevent double-claiming of rewards and refunds.
         require!(
             !prediction.reward_claimed,
@@ -306,25 +529,54 @@ pub mod cyphercast {
         );
         require!(!prediction.refunded, CypherCastError::RefundAlreadyClaimed);
 
-        // Proportional reward: user's stake share of the total winning stake over entire vault pool
-        let winner_total = stream.total_by_choice[stream.winning_choice as usize];
-        require!(winner_total > 0, CypherCastError::NoWinner);
+        // Use distributable pool after streamer tip, plus any sponsor bonus
+        let distributable = stream.distributable(ctx.accounts.vault.total_deposited)?;
 
-        // Use distributable pool after streamer tip
-        let distributable = ctx
-            .accounts
-            .vault
-            .total_deposited
-            .checked_sub(stream.tip_amount)
-            .ok_or(CypherCastError::Overflow)?;
-        let reward_amount = if distributable == 0 {
-            0
-        } else {
-            (distributable as u128)
-                .checked_mul(prediction.stake_amount as u128)
-                .ok_or(CypherCastError::Overflow)?
-                .checked_div(winner_total as u128)
-                .ok_or(CypherCastError::Overflow)? as u64
+        let reward_amount = match stream.market_mode {
+            MarketMode::Parimutuel | MarketMode::FixedOdds => {
+                // Only predictions on a winning choice can claim rewards.
+                require!(
+                    stream.winner_weights[prediction.choice as usize] > 0,
+                    CypherCastError::NotWinner
+                );
+
+                if stream.market_mode == MarketMode::FixedOdds {
+                    // Fixed odds: the house pays stake * odds regardless of the pool split
+                    stream.fixed_odds_payout(prediction.choice, prediction.stake_amount)?
+                } else {
+                    // Proportional reward: stake share of the choice's weighted slice of the pool
+                    stream.choice_payout(
+                        distributable,
+                        prediction.choice,
+                        prediction.stake_amount,
+                    )?
+                }
+            }
+            MarketMode::FreeToPlay => {
+                require!(
+                    stream.winner_weights[prediction.choice as usize] > 0,
+                    CypherCastError::NotWinner
+                );
+                // Free entries carry no stake; the prize is split by entry weight
+                let entry_weight =
+                    u64::try_from(prediction.weight).map_err(|_| CypherCastError::Overflow)?;
+                stream.choice_payout(distributable, prediction.choice, entry_weight)?
+            }
+            MarketMode::Scalar | MarketMode::Ranked => {
+                // Weights are only final once every prediction has been tallied
+                require!(
+                    stream.tallied_count == stream.prediction_count,
+                    CypherCastError::TallyIncomplete
+                );
+                require!(stream.total_weight > 0, CypherCastError::NoWinner);
+                require!(prediction.weight > 0, CypherCastError::NotWinner);
+                pro_rata(distributable, prediction.weight, stream.total_weight)?
+            }
+            // LMSR positions settle through `redeem_shares`, outcome tokens through
+            // `redeem_outcome_tokens`
+            MarketMode::Lmsr | MarketMode::OutcomeTokens => {
+                return err!(CypherCastError::UnsupportedMarketMode)
+            }
         };
 
         // Transfer tokens from vault to winner using PDA signer
@@ -371,9 +623,9 @@ pub mod cyphercast {
     pub fn activate_stream(ctx: Context<ActivateStream>) -> Result<()> {
         let stream = &mut ctx.accounts.stream;
 
-        // Only creator can activate; cannot activate twice; cannot activate if canceled/resolved
+        // Only owner can activate; cannot activate twice; cannot activate if canceled/resolved
         require!(
-            stream.creator == *ctx.accounts.creator.key,
+            stream.owner == *ctx.accounts.creator.key,
             CypherCastError::Unauthorized
         );
         require!(stream.canceled_at == 0, CypherCastError::Canceled);
@@ -382,6 +634,21 @@ pub mod cyphercast {
             stream.config_hash == [0u8; 32],
             CypherCastError::AlreadyActivated
         );
+        require!(
+            stream.bond_amount == 0 || stream.bond_posted,
+            CypherCastError::BondNotPosted
+        );
+        // The market mode may have changed since `configure_stake_limits`
+        stream.validate_stake_limits()?;
+
+        // Metadata, once created, is frozen together with the config
+        let metadata = match ctx.accounts.stream_metadata.as_deref() {
+            Some(metadata) => metadata.try_to_vec()?,
+            None => {
+                require!(!stream.has_metadata, CypherCastError::MetadataRequired);
+                Vec::new()
+            }
+        };
 
         // Compute config hash to freeze settings
         let h = anchor_lang::solana_program::hash::hashv(&[
@@ -390,6 +657,38 @@ pub mod cyphercast {
             &[stream.precision],
             &stream.lock_offset_secs.to_le_bytes(),
             &stream.grace_period_secs.to_le_bytes(),
+            &[stream.market_mode as u8],
+            &stream
+                .odds_bps
+                .iter()
+                .flat_map(|o| o.to_le_bytes())
+                .collect::<Vec<u8>>(),
+            &stream.lmsr_b.to_le_bytes(),
+            &[stream.lmsr_outcomes],
+            &stream.scalar_min.to_le_bytes(),
+            &stream.scalar_max.to_le_bytes(),
+            &stream.scalar_scoring.try_to_vec()?,
+            &[stream.rank_depth],
+            &stream.rank_points.try_to_vec()?,
+            &[stream.free_play_split as u8],
+            &stream.gate.try_to_vec()?,
+            &stream.entry_fee.to_le_bytes(),
+            &stream.entry_fee_split.try_to_vec()?,
+            &stream.allowlist_root,
+            &stream.min_stake.to_le_bytes(),
+            &stream.max_stake_per_viewer.to_le_bytes(),
+            &stream.max_share_bps_of_choice.to_le_bytes(),
+            &stream.share_limit_min_pool.to_le_bytes(),
+            &stream.min_participants.to_le_bytes(),
+            &stream.min_total_stake.to_le_bytes(),
+            &[stream.require_two_sided as u8],
+            &stream.max_lock_extension_secs.to_le_bytes(),
+            &stream.withdrawal_window_secs.to_le_bytes(),
+            &stream.min_observation_secs.to_le_bytes(),
+            &stream.bond_amount.to_le_bytes(),
+            &[stream.bond_slash_target as u8],
+            stream.arbiter.as_ref(),
+            &metadata,
         ]);
         stream.config_hash = h.to_bytes();
 
@@ -400,16 +699,36 @@ pub mod cyphercast {
     pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
         let stream = &mut ctx.accounts.stream;
 
-        // Only creator; cannot cancel twice; cannot cancel after resolve
+        // Only owner; cannot cancel twice; cannot cancel after resolve
         require!(
-            stream.creator == *ctx.accounts.creator.key,
+            stream.owner == *ctx.accounts.creator.key,
             CypherCastError::Unauthorized
         );
         require!(stream.canceled_at == 0, CypherCastError::AlreadyCanceled);
         require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
 
+        // After the lock the creator may only cancel by forfeiting a posted bond;
+        // otherwise cancellation is left to the arbiter.
+        let now = Clock::get()?.unix_timestamp;
+        if now >= stream.lock_time()? {
+            require!(stream.bond_posted, CypherCastError::LateCancelRequiresBond);
+            stream.bond_slashed = true;
+
+            emit!(BondSlashed {
+                stream: stream.key(),
+                amount: stream.bond_amount,
+                target: stream.bond_slash_target
+            });
+        }
+
+        let profile = &mut ctx.accounts.creator_profile;
+        profile.streams_canceled = profile
+            .streams_canceled
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+
         stream.is_active = false;
-        stream.canceled_at = Clock::get()?.unix_timestamp;
+        stream.canceled_at = now;
 
         msg!(
             "Stream {} canceled at {}",
@@ -419,12 +738,36 @@ pub mod cyphercast {
         Ok(())
     }
 
+    /// Cancel a stream on behalf of the viewers, at any time before resolution. The
+    /// creator bond is never slashed here.
+    pub fn arbiter_cancel_stream(ctx: Context<ArbiterCancelStream>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.arbiter != Pubkey::default() && stream.arbiter == *ctx.accounts.arbiter.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.canceled_at == 0, CypherCastError::AlreadyCanceled);
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+
+        stream.is_active = false;
+        stream.canceled_at = Clock::get()?.unix_timestamp;
+
+        msg!(
+            "Stream {} canceled by arbiter {} at {}",
+            stream.stream_id,
+            stream.arbiter,
+            stream.canceled_at
+        );
+        Ok(())
+    }
+
     pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
         let prediction = &mut ctx.accounts.prediction;
-        let stream = &ctx.accounts.stream;
+        let stream = &mut ctx.accounts.stream;
 
-        // Refunds only allowed if stream canceled
-        require!(stream.canceled_at != 0, CypherCastError::Canceled);
+        // Refunds only allowed if stream canceled or resolved as void
+        require!(stream.is_refund_mode(), CypherCastError::Canceled);
         // Ensure not already claimed reward/refund
         require!(
             !prediction.reward_claimed,
@@ -459,6 +802,36 @@ pub mod cyphercast {
             .ok_or(CypherCastError::Overflow)?;
         prediction.refunded = true;
 
+        // Slashed creator bonds are shared pro rata to refundable prediction stake; the
+        // remainder is swept by `distribute_slashed_bond` once refunds are done or the claim
+        // window closes, after which refunds return the stake only
+        if stream.bond_slashed
+            && !stream.bond_released
+            && stream.bond_slash_target == BondSlashTarget::Predictors
+            && prediction.stake_amount > 0
+        {
+            let bond_share = pro_rata(
+                stream.bond_amount,
+                prediction.stake_amount,
+                stream.bond_refundable_stake() as u128,
+            )?;
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.vault,
+                &ctx.accounts.vault_token_account,
+                &ctx.accounts.viewer_token_account,
+                bond_share,
+            )?;
+            stream.bond_distributed = stream
+                .bond_distributed
+                .checked_add(bond_share)
+                .ok_or(CypherCastError::Overflow)?;
+            stream.refunded_stake = stream
+                .refunded_stake
+                .checked_add(prediction.stake_amount)
+                .ok_or(CypherCastError::Overflow)?;
+        }
+
         msg!(
             "Refund of {} tokens returned to {}",
             amount,
@@ -466,383 +839,6173 @@ pub mod cyphercast {
         );
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(stream_id: u64)]
-pub struct CreateStream<'info> {
-    #[account(
-        init,
-        payer = creator,
-        space = Stream::SPACE,
-        seeds = [b"stream", creator.key().as_ref(), stream_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub stream: Account<'info, Stream>,
+    /// Switch a Draft stream to fixed-odds (house) mode.
+    /// `odds_bps` is the payout multiplier per choice in basis points (25_000 = 2.5x);
+    /// a zero entry closes that choice.
+    pub fn configure_fixed_odds(
+        ctx: Context<ConfigureFixedOdds>,
+        odds_bps: [u32; 11],
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
 
-    #[account(mut)]
-    pub creator: Signer<'info>,
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(stream.total_stake == 0, CypherCastError::InvalidConfig);
+        // Every open choice must at least return the stake
+        require!(
+            odds_bps.iter().all(|&o| o == 0 || o >= 10_000),
+            CypherCastError::InvalidOdds
+        );
+        require!(
+            odds_bps.iter().any(|&o| o > 0),
+            CypherCastError::InvalidOdds
+        );
 
-    pub system_program: Program<'info, System>,
-}
+        stream.market_mode = MarketMode::FixedOdds;
+        stream.odds_bps = odds_bps;
 
-#[derive(Accounts)]
-pub struct InitializeCommunityVault<'info> {
-    #[account(mut)]
-    pub creator: Signer<'info>,
+        msg!("Stream {} configured for fixed odds", stream.stream_id);
+        Ok(())
+    }
 
-    /// Minimal DAO authority for now (can be creator)
-    pub dao_authority: Signer<'info>,
+    /// Creator deposits tokens into the vault to underwrite fixed-odds payouts.
+    pub fn fund_house_reserve(ctx: Context<FundHouseReserve>, amount: u64) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
 
-    #[account(
-        init,
-        seeds = [b"community_vault"],
-        bump,
-        payer = creator,
-        space = CommunityVault::SPACE
-    )]
-    pub community_vault: Account<'info, CommunityVault>,
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(
+            stream.is_house_backed(),
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+        require!(amount > 0, CypherCastError::InvalidStakeAmount);
 
-    pub token_mint: Account<'info, Mint>,
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.creator_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, amount)?;
 
-    #[account(
-        init,
-        payer = creator,
-        associated_token::mint = token_mint,
-        associated_token::authority = community_vault
-    )]
-    pub community_vault_token_account: Account<'info, TokenAccount>,
+        stream.house_reserve = stream
+            .house_reserve
+            .checked_add(amount)
+            .ok_or(CypherCastError::Overflow)?;
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-}
+        msg!(
+            "House reserve for stream {} funded with {} (total {})",
+            stream.stream_id,
+            amount,
+            stream.house_reserve
+        );
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct InitializeTokenVault<'info> {
-    #[account(mut)]
-    pub creator: Signer<'info>,
+    /// Creator reclaims whatever the house did not owe once the stream is settled:
+    /// `reserve + stakes - liability[winner]` after resolution, the full reserve after cancel.
+    /// For LMSR markets the liability is the winning share count, and after cancel the
+    /// reserve covers refunds of position cost bases.
+    pub fn withdraw_house_reserve(ctx: Context<WithdrawHouseReserve>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
 
-    #[account(
-        mut,
-        seeds = [b"stream", creator.key().as_ref(), stream.stream_id.to_le_bytes().as_ref()],
-        bump = stream.bump,
-    )]
-    pub stream: Account<'info, Stream>,
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(
+            stream.is_house_backed(),
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(
+            !stream.house_settled,
+            CypherCastError::ReserveAlreadySettled
+        );
 
-    #[account(
-        init,
-        seeds = [b"vault", stream.key().as_ref()],
-        bump,
-        payer = creator,
-        space = TokenVault::SPACE,
-    )]
-    pub vault: Account<'info, TokenVault>,
+        let amount = match (stream.market_mode, stream.is_refund_mode()) {
+            (MarketMode::Lmsr, true) => {
+                let funds = stream.lmsr_refund_funds()?;
+                funds - funds.min(stream.refund_liability)
+            }
+            (_, true) => stream.house_reserve,
+            (_, false) => {
+                require!(stream.is_resolved, CypherCastError::NotResolved);
+                let winner = stream.winning_choice as usize;
+                let liability = if stream.market_mode == MarketMode::Lmsr {
+                    stream.shares_by_choice[winner]
+                } else {
+                    stream.liability_by_choice[winner]
+                };
+                stream
+                    .house_reserve
+                    .checked_add(stream.total_stake)
+                    .ok_or(CypherCastError::Overflow)?
+                    .checked_sub(liability)
+                    .ok_or(CypherCastError::Overflow)?
+            }
+        };
 
-    pub token_mint: Account<'info, Mint>,
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.creator_token_account,
+            amount,
+        )?;
+        stream.house_settled = true;
 
-    #[account(
-        init,
-        payer = creator,
-        associated_token::mint = token_mint,
-        associated_token::authority = vault,
-    )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+        msg!(
+            "House reserve of {} tokens returned to creator of stream {}",
+            amount,
+            stream.stream_id
+        );
+        Ok(())
+    }
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-}
+    /// Switch a Draft stream to an LMSR market over `outcomes` choices with liquidity `b`.
+    /// The creator must fund at least `b * ln(outcomes)` via `fund_house_reserve` before trading.
+    pub fn configure_lmsr(ctx: Context<ConfigureLmsr>, b: u64, outcomes: u8) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
 
-#[derive(Accounts)]
-pub struct JoinStream<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(stream.total_stake == 0, CypherCastError::InvalidConfig);
+        require!(b > 0, CypherCastError::InvalidConfig);
+        require!(
+            (2..=MAX_CHOICES + 1).contains(&outcomes),
+            CypherCastError::InvalidConfig
+        );
 
-    #[account(
-        init,
-        payer = viewer,
-        space = Participant::SPACE,
-        seeds = [b"participant", stream.key().as_ref(), viewer.key().as_ref()],
-        bump
-    )]
-    pub participant: Account<'info, Participant>,
+        stream.market_mode = MarketMode::Lmsr;
+        stream.lmsr_b = b;
+        stream.lmsr_outcomes = outcomes;
 
-    #[account(mut)]
-    pub viewer: Signer<'info>,
+        msg!(
+            "Stream {} configured as LMSR market (b = {}, outcomes = {})",
+            stream.stream_id,
+            b,
+            outcomes
+        );
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    /// Quote an LMSR trade without executing it. Returns the cost of buying or the
+    /// proceeds of selling `shares` of `choice` via return data.
+    pub fn quote_lmsr(ctx: Context<QuoteLmsr>, choice: u8, shares: u64, buy: bool) -> Result<u64> {
+        let stream = &ctx.accounts.stream;
 
-#[derive(Accounts)]
-pub struct SubmitPrediction<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+        require!(
+            stream.market_mode == MarketMode::Lmsr,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(
+            choice < stream.lmsr_outcomes,
+            CypherCastError::InvalidChoice
+        );
 
-    #[account(
-        init,
-        payer = viewer,
-        space = Prediction::SPACE,
-        seeds = [b"prediction", stream.key().as_ref(), viewer.key().as_ref()],
-        bump
-    )]
-    pub prediction: Account<'info, Prediction>,
+        stream.lmsr_quote(choice, shares, buy)
+    }
 
-    #[account(
-        mut,
-        seeds = [b"vault", stream.key().as_ref()],
-        bump = vault.bump,
-    )]
-    pub vault: Account<'info, TokenVault>,
+    /// Create the per-viewer position account that holds LMSR shares.
+    pub fn open_lmsr_position(ctx: Context<OpenLmsrPosition>) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        let position = &mut ctx.accounts.position;
+
+        require!(
+            stream.market_mode == MarketMode::Lmsr,
+            CypherCastError::UnsupportedMarketMode
+        );
+
+        position.stream = stream.key();
+        position.viewer = *ctx.accounts.viewer.key;
+        position.shares = [0; 11];
+        position.cost_basis = 0;
+        position.redeemed = false;
+        position.bump = ctx.bumps.position;
+        Ok(())
+    }
+
+    /// Buy `shares` of `choice`, failing if the cost exceeds `max_cost`. Returns the cost paid.
+    pub fn buy_shares(
+        ctx: Context<TradeShares>,
+        choice: u8,
+        shares: u64,
+        max_cost: u64,
+    ) -> Result<u64> {
+        let stream = &mut ctx.accounts.stream;
+        let position = &mut ctx.accounts.position;
+
+        stream.require_lmsr_trading(choice, shares)?;
+        require_access_pass(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.access_pass.as_ref(),
+        )?;
+        require_gate_passed(stream, ctx.accounts.participant.as_deref())?;
+        require!(
+            stream.house_reserve
+                >= lmsr::max_loss(stream.lmsr_b, stream.lmsr_outcomes)
+                    .ok_or(CypherCastError::Overflow)?,
+            CypherCastError::ReserveExceeded
+        );
+
+        // Every buy costs at least one base unit so deep underdogs are never free
+        let cost = stream.lmsr_quote(choice, shares, true)?.max(1);
+        require!(cost <= max_cost, CypherCastError::SlippageExceeded);
+        let opens_position = position.shares.iter().all(|&held| held == 0);
+
+        // Limits count the tokens paid; the share is of the choice's outstanding shares,
+        // with the market maker's depth `b` counted as liquidity
+        record_viewer_stake(stream, ctx.accounts.participant.as_deref_mut(), cost)?;
+        let idx = choice as usize;
+        require_choice_share(
+            stream,
+            position.shares[idx]
+                .checked_add(shares)
+                .ok_or(CypherCastError::Overflow)?,
+            stream.shares_by_choice[idx]
+                .checked_add(shares)
+                .and_then(|s| s.checked_add(stream.lmsr_b))
+                .ok_or(CypherCastError::Overflow)?,
+        )?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.viewer_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.viewer.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, cost)?;
+
+        ctx.accounts.vault.total_deposited = ctx
+            .accounts
+            .vault
+            .total_deposited
+            .checked_add(cost)
+            .ok_or(CypherCastError::Overflow)?;
+
+        stream.shares_by_choice[idx] = stream.shares_by_choice[idx]
+            .checked_add(shares)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.total_stake = stream
+            .total_stake
+            .checked_add(cost)
+            .ok_or(CypherCastError::Overflow)?;
+        position.shares[idx] = position.shares[idx]
+            .checked_add(shares)
+            .ok_or(CypherCastError::Overflow)?;
+        let cost_basis = position
+            .cost_basis
+            .checked_add(cost)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.update_refund_liability(position.cost_basis, cost_basis)?;
+        position.cost_basis = cost_basis;
+        // Each open position counts once toward `min_participants`
+        if opens_position {
+            stream.prediction_count = stream
+                .prediction_count
+                .checked_add(1)
+                .ok_or(CypherCastError::Overflow)?;
+        }
+
+        emit!(SharesTraded {
+            stream: stream.key(),
+            viewer: position.viewer,
+            choice,
+            shares,
+            amount: cost,
+            buy: true
+        });
+
+        Ok(cost)
+    }
+
+    /// Sell `shares` of `choice` back to the market, failing if proceeds fall below
+    /// `min_proceeds`. Returns the proceeds received.
+    pub fn sell_shares(
+        ctx: Context<TradeShares>,
+        choice: u8,
+        shares: u64,
+        min_proceeds: u64,
+    ) -> Result<u64> {
+        let stream = &mut ctx.accounts.stream;
+        let position = &mut ctx.accounts.position;
+
+        stream.require_lmsr_trading(choice, shares)?;
+        let idx = choice as usize;
+        require!(
+            position.shares[idx] >= shares,
+            CypherCastError::InsufficientShares
+        );
+
+        let proceeds = stream.lmsr_quote(choice, shares, false)?;
+        require!(proceeds >= min_proceeds, CypherCastError::SlippageExceeded);
+
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.viewer_token_account,
+            proceeds,
+        )?;
+
+        stream.shares_by_choice[idx] -= shares;
+        stream.total_stake = stream
+            .total_stake
+            .checked_sub(proceeds)
+            .ok_or(CypherCastError::Overflow)?;
+        position.shares[idx] -= shares;
+        let cost_basis = position.cost_basis.saturating_sub(proceeds);
+        stream.update_refund_liability(position.cost_basis, cost_basis)?;
+        position.cost_basis = cost_basis;
+        if let Some(participant) = ctx.accounts.participant.as_mut() {
+            participant.stake_amount = participant.stake_amount.saturating_sub(proceeds);
+        }
+        if position.shares.iter().all(|&held| held == 0) {
+            stream.prediction_count = stream
+                .prediction_count
+                .checked_sub(1)
+                .ok_or(CypherCastError::Overflow)?;
+        }
+
+        emit!(SharesTraded {
+            stream: stream.key(),
+            viewer: position.viewer,
+            choice,
+            shares,
+            amount: proceeds,
+            buy: false
+        });
+
+        Ok(proceeds)
+    }
+
+    /// Redeem an LMSR position: one token unit per winning share after resolution,
+    /// or the position's net cost basis if the stream was canceled.
+    pub fn redeem_shares(ctx: Context<RedeemShares>) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        let position = &mut ctx.accounts.position;
+
+        require!(
+            stream.market_mode == MarketMode::Lmsr,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(!position.redeemed, CypherCastError::RewardAlreadyClaimed);
+
+        let amount = if stream.is_refund_mode() {
+            stream.lmsr_refund(position.cost_basis)?
+        } else {
+            require!(stream.is_resolved, CypherCastError::NotResolved);
+            position.shares[stream.winning_choice as usize]
+        };
+
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.viewer_token_account,
+            amount,
+        )?;
+        position.redeemed = true;
+
+        msg!(
+            "LMSR position of {} redeemed for {} tokens",
+            position.viewer,
+            amount
+        );
+
+        emit!(RewardClaimed {
+            stream: stream.key(),
+            viewer: position.viewer,
+            amount
+        });
+
+        Ok(())
+    }
+
+    /// Switch a Draft stream to tokenized positions: stakes mint per-choice SPL outcome
+    /// tokens that any holder can redeem after resolution.
+    pub fn configure_outcome_tokens(ctx: Context<ConfigureOutcomeTokens>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(stream.total_stake == 0, CypherCastError::InvalidConfig);
+
+        stream.market_mode = MarketMode::OutcomeTokens;
+
+        msg!("Stream {} configured for outcome tokens", stream.stream_id);
+        Ok(())
+    }
+
+    /// Create the outcome token mint for `choice`. The stream PDA is the mint authority
+    /// and the mint shares the vault mint's decimals so one outcome token = one staked unit.
+    pub fn create_outcome_mint(ctx: Context<CreateOutcomeMint>, choice: u8) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(
+            stream.market_mode == MarketMode::OutcomeTokens,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(choice <= MAX_CHOICES, CypherCastError::InvalidChoice);
+
+        msg!(
+            "Outcome mint {} created for choice {} of stream {}",
+            ctx.accounts.outcome_mint.key(),
+            choice,
+            stream.stream_id
+        );
+        Ok(())
+    }
+
+    /// Stake on `choice` and receive the same amount of that choice's outcome tokens.
+    /// Can be called repeatedly to add to a position.
+    pub fn submit_tokenized_prediction(
+        ctx: Context<SubmitTokenizedPrediction>,
+        choice: u8,
+        stake_amount: u64,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let vault = &mut ctx.accounts.vault;
+
+        require!(
+            stream.market_mode == MarketMode::OutcomeTokens,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(stream.is_active, CypherCastError::StreamNotActive);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(stake_amount > 0, CypherCastError::InvalidStakeAmount);
+        require!(choice <= MAX_CHOICES, CypherCastError::InvalidChoice);
+        require_access_pass(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.access_pass.as_ref(),
+        )?;
+        require_gate_passed(stream, ctx.accounts.participant.as_deref())?;
+        enforce_stake_limits(
+            stream,
+            ctx.accounts.participant.as_deref_mut(),
+            Some(choice),
+            stake_amount,
+        )?;
+        // Each top-up is a separate submission and counts toward `min_participants`
+        stream.prediction_count = stream
+            .prediction_count
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+        let now = Clock::get()?.unix_timestamp;
+        stream.require_open_for_predictions(now)?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.viewer_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.viewer.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, stake_amount)?;
+
+        // Mint outcome tokens 1:1 with the stake, signed by the stream PDA
+        let stream_id_bytes = stream.stream_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"stream",
+            stream.creator.as_ref(),
+            stream_id_bytes.as_ref(),
+            &[stream.bump],
+        ]];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.outcome_mint.to_account_info(),
+                to: ctx.accounts.viewer_outcome_account.to_account_info(),
+                authority: stream.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::mint_to(cpi_ctx, stake_amount)?;
+
+        vault.total_deposited = vault
+            .total_deposited
+            .checked_add(stake_amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.total_stake = stream
+            .total_stake
+            .checked_add(stake_amount)
+            .ok_or(CypherCastError::Overflow)?;
+        let idx = choice as usize;
+        stream.total_by_choice[idx] = stream.total_by_choice[idx]
+            .checked_add(stake_amount)
+            .ok_or(CypherCastError::Overflow)?;
+
+        emit!(PredictionSubmitted {
+            stream: stream.key(),
+            viewer: *ctx.accounts.viewer.key,
+            choice,
+            amount: stake_amount
+        });
+
+        Ok(())
+    }
+
+    /// Burn `amount` outcome tokens of `choice` and pay the bearer. After resolution winning
+    /// tokens pay their proportional share of the pool and losing tokens are worthless; after
+    /// cancellation every token refunds one staked unit.
+    pub fn redeem_outcome_tokens(
+        ctx: Context<RedeemOutcomeTokens>,
+        choice: u8,
+        amount: u64,
+    ) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+
+        require!(
+            stream.market_mode == MarketMode::OutcomeTokens,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(amount > 0, CypherCastError::InvalidStakeAmount);
+
+        let payout = if stream.is_refund_mode() {
+            amount
+        } else {
+            require!(stream.is_resolved, CypherCastError::NotResolved);
+            require!(
+                stream.winner_weights[choice as usize] > 0,
+                CypherCastError::NotWinner
+            );
+            let distributable = stream.distributable(ctx.accounts.vault.total_deposited)?;
+            stream.choice_payout(distributable, choice, amount)?
+        };
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.outcome_mint.to_account_info(),
+                from: ctx.accounts.holder_outcome_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        );
+        token::burn(cpi_ctx, amount)?;
+
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.holder_token_account,
+            payout,
+        )?;
+
+        msg!(
+            "{} outcome tokens of choice {} redeemed for {}",
+            amount,
+            choice,
+            payout
+        );
+
+        emit!(RewardClaimed {
+            stream: stream.key(),
+            viewer: *ctx.accounts.holder.key,
+            amount: payout
+        });
+
+        Ok(())
+    }
+
+    /// Open a 1v1 challenge on a stream moment. The challenger escrows `stake_amount` on
+    /// `choice`; `counterparty` optionally restricts who may accept. Challenge escrow lives
+    /// in the stream vault but is kept out of the pool totals.
+    pub fn open_challenge(
+        ctx: Context<OpenChallenge>,
+        challenge_id: u64,
+        choice: u8,
+        stake_amount: u64,
+        counterparty: Option<Pubkey>,
+    ) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        let challenge = &mut ctx.accounts.challenge;
+
+        require!(stream.is_active, CypherCastError::StreamNotActive);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(stake_amount > 0, CypherCastError::InvalidStakeAmount);
+        require!(choice <= MAX_CHOICES, CypherCastError::InvalidChoice);
+        require!(
+            stream.resolves_to_choice(),
+            CypherCastError::UnsupportedMarketMode
+        );
+        require_access_pass(
+            stream,
+            ctx.accounts.challenger.key,
+            ctx.accounts.access_pass.as_ref(),
+        )?;
+        require_gate_passed(stream, ctx.accounts.participant.as_deref())?;
+        // Escrowed outside the pool, so only the per-viewer limits apply
+        record_viewer_stake(
+            stream,
+            ctx.accounts.participant.as_deref_mut(),
+            stake_amount,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        stream.require_open_for_predictions(now)?;
+        if let Some(counterparty) = counterparty {
+            require!(
+                counterparty != *ctx.accounts.challenger.key,
+                CypherCastError::InvalidCounterparty
+            );
+        }
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.challenger_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.challenger.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, stake_amount)?;
+
+        challenge.stream = stream.key();
+        challenge.challenger = *ctx.accounts.challenger.key;
+        challenge.challenge_id = challenge_id;
+        challenge.counterparty = counterparty.unwrap_or_default();
+        challenge.acceptor = Pubkey::default();
+        challenge.choice = choice;
+        challenge.stake_amount = stake_amount;
+        challenge.created_at = now;
+        challenge.accepted_at = 0;
+        challenge.bump = ctx.bumps.challenge;
+
+        emit!(ChallengeOpened {
+            stream: stream.key(),
+            challenge: challenge.key(),
+            challenger: challenge.challenger,
+            counterparty: challenge.counterparty,
+            choice,
+            amount: stake_amount
+        });
+
+        Ok(())
+    }
+
+    /// Accept an open challenge by matching its stake on the opposite side: the acceptor
+    /// wins unless the stream resolves to the challenger's choice.
+    pub fn accept_challenge(ctx: Context<AcceptChallenge>) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        let challenge = &mut ctx.accounts.challenge;
+        let acceptor = *ctx.accounts.acceptor.key;
+
+        require!(stream.is_active, CypherCastError::StreamNotActive);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(
+            challenge.acceptor == Pubkey::default(),
+            CypherCastError::ChallengeAlreadyAccepted
+        );
+        require!(
+            acceptor != challenge.challenger,
+            CypherCastError::InvalidCounterparty
+        );
+        require!(
+            challenge.counterparty == Pubkey::default() || challenge.counterparty == acceptor,
+            CypherCastError::InvalidCounterparty
+        );
+        require_access_pass(stream, &acceptor, ctx.accounts.access_pass.as_ref())?;
+        require_gate_passed(stream, ctx.accounts.participant.as_deref())?;
+        record_viewer_stake(
+            stream,
+            ctx.accounts.participant.as_deref_mut(),
+            challenge.stake_amount,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        stream.require_open_for_predictions(now)?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.acceptor_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.acceptor.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, challenge.stake_amount)?;
+
+        challenge.acceptor = acceptor;
+        challenge.accepted_at = now;
+
+        emit!(ChallengeAccepted {
+            stream: stream.key(),
+            challenge: challenge.key(),
+            acceptor,
+            amount: challenge.stake_amount
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw an unaccepted challenge; the challenger gets the full stake back and the
+    /// challenge account is closed.
+    pub fn cancel_challenge(ctx: Context<CancelChallenge>) -> Result<()> {
+        let challenge = &ctx.accounts.challenge;
+
+        require!(
+            challenge.acceptor == Pubkey::default(),
+            CypherCastError::ChallengeAlreadyAccepted
+        );
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.challenger_token_account,
+            challenge.stake_amount,
+        )?;
+        if let Some(participant) = ctx.accounts.participant.as_mut() {
+            participant.stake_amount = participant
+                .stake_amount
+                .saturating_sub(challenge.stake_amount);
+        }
+
+        msg!(
+            "Challenge {} canceled, {} refunded to {}",
+            challenge.challenge_id,
+            challenge.stake_amount,
+            challenge.challenger
+        );
+        Ok(())
+    }
+
+    /// Settle an accepted challenge from the stream's resolution (permissionless). The winner
+    /// takes both stakes (the challenger wins if their choice is among the winners); if the
+    /// stream was canceled or voided each side is refunded.
+    pub fn settle_challenge(ctx: Context<SettleChallenge>) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        let challenge = &ctx.accounts.challenge;
+
+        require!(
+            challenge.acceptor != Pubkey::default(),
+            CypherCastError::ChallengeNotAccepted
+        );
+
+        let stake = challenge.stake_amount;
+        let (challenger_payout, acceptor_payout) = if stream.is_refund_mode() {
+            (stake, stake)
+        } else {
+            require!(stream.is_resolved, CypherCastError::NotResolved);
+            let pot = stake.checked_mul(2).ok_or(CypherCastError::Overflow)?;
+            if stream.winner_weights[challenge.choice as usize] > 0 {
+                (pot, 0)
+            } else {
+                (0, pot)
+            }
+        };
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.challenger_token_account,
+            challenger_payout,
+        )?;
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.acceptor_token_account,
+            acceptor_payout,
+        )?;
+
+        emit!(ChallengeSettled {
+            stream: stream.key(),
+            challenge: challenge.key(),
+            challenger_payout,
+            acceptor_payout
+        });
+
+        Ok(())
+    }
+
+    /// Switch a Draft stream to a scalar market: viewers predict a value in `[min, max]`,
+    /// expressed in fixed point with `precision` decimals, scored by `scoring`.
+    pub fn configure_scalar(
+        ctx: Context<ConfigureScalar>,
+        min: i64,
+        max: i64,
+        scoring: ScalarScoring,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(stream.total_stake == 0, CypherCastError::InvalidConfig);
+        require!(min < max, CypherCastError::InvalidConfig);
+        if let ScalarScoring::WithinBand { band } = scoring {
+            require!(band <= max.abs_diff(min), CypherCastError::InvalidConfig);
+        }
+
+        stream.market_mode = MarketMode::Scalar;
+        stream.scalar_min = min;
+        stream.scalar_max = max;
+        stream.scalar_scoring = scoring;
+
+        msg!(
+            "Stream {} configured as scalar market [{}, {}]",
+            stream.stream_id,
+            min,
+            max
+        );
+        Ok(())
+    }
+
+    /// Stake on a predicted value for a scalar market.
+    pub fn submit_scalar_prediction(
+        ctx: Context<SubmitPrediction>,
+        value: i64,
+        stake_amount: u64,
+    ) -> Result<()> {
+        let prediction = &mut ctx.accounts.prediction;
+        let stream = &mut ctx.accounts.stream;
+        let vault = &mut ctx.accounts.vault;
+
+        require!(
+            stream.market_mode == MarketMode::Scalar,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(stream.is_active, CypherCastError::StreamNotActive);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(stake_amount > 0, CypherCastError::InvalidStakeAmount);
+        require_access_pass(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.access_pass.as_ref(),
+        )?;
+        enforce_stake_limits(
+            stream,
+            ctx.accounts.participant.as_deref_mut(),
+            None,
+            stake_amount,
+        )?;
+        verify_gate(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.gate_token_account.as_ref(),
+            ctx.accounts.gate_metadata.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+        require!(
+            (stream.scalar_min..=stream.scalar_max).contains(&value),
+            CypherCastError::ValueOutOfRange
+        );
+        let now = Clock::get()?.unix_timestamp;
+        stream.require_open_for_predictions(now)?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.viewer_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.viewer.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, stake_amount)?;
+
+        vault.total_deposited = vault
+            .total_deposited
+            .checked_add(stake_amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.total_stake = stream
+            .total_stake
+            .checked_add(stake_amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.prediction_count = stream
+            .prediction_count
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+
+        prediction.stream = stream.key();
+        prediction.viewer = *ctx.accounts.viewer.key;
+        prediction.choice = 0;
+        prediction.stake_amount = stake_amount;
+        prediction.timestamp = now;
+        prediction.reward_claimed = false;
+        prediction.scalar_value = value;
+        prediction.ranking = [0; MAX_RANK_DEPTH];
+        prediction.weight = 0;
+        prediction.tallied = false;
+        prediction.bump = ctx.bumps.prediction;
+
+        msg!(
+            "Scalar prediction submitted: value {} with stake {} tokens by {}",
+            value,
+            stake_amount,
+            prediction.viewer
+        );
+
+        emit!(ScalarPredictionSubmitted {
+            stream: stream.key(),
+            viewer: prediction.viewer,
+            value,
+            amount: stake_amount
+        });
+
+        Ok(())
+    }
+
+    /// Resolve a scalar market with the observed value. Rewards become claimable once
+    /// every prediction has been tallied with `tally_prediction`.
+    pub fn resolve_scalar(ctx: Context<ResolvePrediction>, actual_value: i64) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+        stream.end_for_resolution(Clock::get()?.unix_timestamp)?;
+        // Streams with participation thresholds must pass `finalize_lock` first
+        require!(
+            stream.lock_finalized || !stream.has_participation_threshold(),
+            CypherCastError::LockNotFinalized
+        );
+        require!(
+            stream.market_mode == MarketMode::Scalar,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(
+            (stream.scalar_min..=stream.scalar_max).contains(&actual_value),
+            CypherCastError::ValueOutOfRange
+        );
+        require_role(
+            stream,
+            ctx.accounts.stream_roles.as_deref(),
+            ctx.accounts.authority.key,
+            StreamRole::Resolver,
+        )?;
+
+        pay_streamer_tip(
+            stream,
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.creator_token_account,
+        )?;
+
+        stream.is_resolved = true;
+        record_resolution(stream, &mut ctx.accounts.creator_profile)?;
+        stream.scalar_result = actual_value;
+        stream.resolved_at = Clock::get()?.unix_timestamp;
+
+        msg!(
+            "Stream {} resolved with value {} (tip: {})",
+            stream.stream_id,
+            actual_value,
+            stream.tip_amount
+        );
+
+        emit!(ScalarResolved {
+            stream: stream.key(),
+            actual_value,
+            tip_amount: stream.tip_amount
+        });
+
+        Ok(())
+    }
+
+    /// Score one prediction against the resolved outcome and add its weight to the stream
+    /// total (permissionless). Claims open once every prediction is tallied, which keeps
+    /// each claim O(1) no matter how many viewers took part.
+    pub fn tally_prediction(ctx: Context<TallyPrediction>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let prediction = &mut ctx.accounts.prediction;
+
+        require!(stream.is_resolved, CypherCastError::NotResolved);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(!prediction.tallied, CypherCastError::AlreadyTallied);
+
+        let weight = stream.prediction_weight(prediction)?;
+
+        stream.total_weight = stream
+            .total_weight
+            .checked_add(weight)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.tallied_count = stream
+            .tallied_count
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+        prediction.weight = weight;
+        prediction.tallied = true;
+
+        msg!(
+            "Prediction by {} tallied with weight {} ({}/{})",
+            prediction.viewer,
+            weight,
+            stream.tallied_count,
+            stream.prediction_count
+        );
+        Ok(())
+    }
+
+    /// Resolve a choice market with one or more winning choices, or void it.
+    /// Each winner's `weight` (default 1) sets its slice of the distributable pool, e.g. two
+    /// winners with equal weight split a tie 50/50. `Void` puts the stream into full-refund
+    /// mode through `claim_refund` without a prior `cancel_stream`, and takes no tip.
+    pub fn resolve_outcome(
+        ctx: Context<ResolvePrediction>,
+        outcome: ResolutionOutcome,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+        stream.end_for_resolution(Clock::get()?.unix_timestamp)?;
+        // Streams with participation thresholds must pass `finalize_lock` first
+        require!(
+            stream.lock_finalized || !stream.has_participation_threshold(),
+            CypherCastError::LockNotFinalized
+        );
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(
+            stream.resolves_to_choice(),
+            CypherCastError::UnsupportedMarketMode
+        );
+        require_role(
+            stream,
+            ctx.accounts.stream_roles.as_deref(),
+            ctx.accounts.authority.key,
+            StreamRole::Resolver,
+        )?;
+
+        match outcome {
+            ResolutionOutcome::Void => {
+                stream.is_void = true;
+                stream.winner_weights = [0; 11];
+            }
+            ResolutionOutcome::Winners(winners) => {
+                require!(!winners.is_empty(), CypherCastError::InvalidChoice);
+                // House-backed payouts are underwritten for a single winning choice only
+                require!(
+                    winners.len() == 1 || !stream.is_house_backed(),
+                    CypherCastError::UnsupportedMarketMode
+                );
+
+                let mut weights = [0u16; 11];
+                for winner in &winners {
+                    require!(winner.choice <= MAX_CHOICES, CypherCastError::InvalidChoice);
+                    require!(
+                        stream.market_mode != MarketMode::Lmsr
+                            || winner.choice < stream.lmsr_outcomes,
+                        CypherCastError::InvalidChoice
+                    );
+                    let slot = &mut weights[winner.choice as usize];
+                    require!(*slot == 0, CypherCastError::DuplicateWinner);
+                    *slot = winner.weight.unwrap_or(1);
+                    require!(*slot > 0, CypherCastError::InvalidWeight);
+                }
+
+                pay_streamer_tip(
+                    stream,
+                    &ctx.accounts.token_program,
+                    &mut ctx.accounts.vault,
+                    &ctx.accounts.vault_token_account,
+                    &ctx.accounts.creator_token_account,
+                )?;
+
+                stream.winner_weights = weights;
+                stream.winning_choice = winners[0].choice;
+            }
+        }
+
+        stream.is_resolved = true;
+        record_resolution(stream, &mut ctx.accounts.creator_profile)?;
+        stream.resolved_at = Clock::get()?.unix_timestamp;
+
+        msg!(
+            "Stream {} resolved (void: {}, tip: {})",
+            stream.stream_id,
+            stream.is_void,
+            stream.tip_amount
+        );
+
+        emit!(OutcomeResolved {
+            stream: stream.key(),
+            winner_weights: stream.winner_weights,
+            is_void: stream.is_void,
+            tip_amount: stream.tip_amount
+        });
+
+        Ok(())
+    }
+
+    /// Switch a Draft stream to a ranked market: viewers order the top `depth` choices and
+    /// score `points.exact` per exact position and `points.in_top` per pick that finished
+    /// elsewhere in the top `depth`.
+    pub fn configure_ranked(
+        ctx: Context<ConfigureRanked>,
+        depth: u8,
+        points: RankPoints,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(stream.total_stake == 0, CypherCastError::InvalidConfig);
+        require!(
+            (1..=MAX_RANK_DEPTH as u8).contains(&depth),
+            CypherCastError::InvalidConfig
+        );
+        require!(
+            points.exact > 0 && points.exact >= points.in_top,
+            CypherCastError::InvalidConfig
+        );
+
+        stream.market_mode = MarketMode::Ranked;
+        stream.rank_depth = depth;
+        stream.rank_points = points;
+
+        msg!(
+            "Stream {} configured as ranked market (top {})",
+            stream.stream_id,
+            depth
+        );
+        Ok(())
+    }
+
+    /// Stake on an ordered list of `rank_depth` distinct choices (first = winner).
+    pub fn submit_ranked_prediction(
+        ctx: Context<SubmitPrediction>,
+        ranking: Vec<u8>,
+        stake_amount: u64,
+    ) -> Result<()> {
+        let prediction = &mut ctx.accounts.prediction;
+        let stream = &mut ctx.accounts.stream;
+        let vault = &mut ctx.accounts.vault;
+
+        require!(
+            stream.market_mode == MarketMode::Ranked,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(stream.is_active, CypherCastError::StreamNotActive);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(stake_amount > 0, CypherCastError::InvalidStakeAmount);
+        require_access_pass(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.access_pass.as_ref(),
+        )?;
+        enforce_stake_limits(
+            stream,
+            ctx.accounts.participant.as_deref_mut(),
+            None,
+            stake_amount,
+        )?;
+        verify_gate(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.gate_token_account.as_ref(),
+            ctx.accounts.gate_metadata.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+        let ranking = stream.parse_ranking(&ranking)?;
+        let now = Clock::get()?.unix_timestamp;
+        stream.require_open_for_predictions(now)?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.viewer_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.viewer.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, stake_amount)?;
+
+        vault.total_deposited = vault
+            .total_deposited
+            .checked_add(stake_amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.total_stake = stream
+            .total_stake
+            .checked_add(stake_amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.prediction_count = stream
+            .prediction_count
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+
+        prediction.stream = stream.key();
+        prediction.viewer = *ctx.accounts.viewer.key;
+        prediction.choice = ranking[0];
+        prediction.stake_amount = stake_amount;
+        prediction.timestamp = now;
+        prediction.reward_claimed = false;
+        prediction.scalar_value = 0;
+        prediction.ranking = ranking;
+        prediction.weight = 0;
+        prediction.tallied = false;
+        prediction.bump = ctx.bumps.prediction;
+
+        msg!(
+            "Ranked prediction submitted with stake {} tokens by {}",
+            stake_amount,
+            prediction.viewer
+        );
+
+        emit!(RankedPredictionSubmitted {
+            stream: stream.key(),
+            viewer: prediction.viewer,
+            ranking: prediction.ranking,
+            amount: stake_amount
+        });
+
+        Ok(())
+    }
+
+    /// Resolve a ranked market with the final top `rank_depth` order. Rewards become
+    /// claimable once every prediction has been tallied with `tally_prediction`.
+    pub fn resolve_ranking(ctx: Context<ResolvePrediction>, ranking: Vec<u8>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+        stream.end_for_resolution(Clock::get()?.unix_timestamp)?;
+        // Streams with participation thresholds must pass `finalize_lock` first
+        require!(
+            stream.lock_finalized || !stream.has_participation_threshold(),
+            CypherCastError::LockNotFinalized
+        );
+        require!(
+            stream.market_mode == MarketMode::Ranked,
+            CypherCastError::UnsupportedMarketMode
+        );
+        let ranking = stream.parse_ranking(&ranking)?;
+        require_role(
+            stream,
+            ctx.accounts.stream_roles.as_deref(),
+            ctx.accounts.authority.key,
+            StreamRole::Resolver,
+        )?;
+
+        pay_streamer_tip(
+            stream,
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.creator_token_account,
+        )?;
+
+        stream.is_resolved = true;
+        record_resolution(stream, &mut ctx.accounts.creator_profile)?;
+        stream.final_ranking = ranking;
+        stream.winning_choice = ranking[0];
+        stream.resolved_at = Clock::get()?.unix_timestamp;
+
+        msg!(
+            "Stream {} resolved with ranking {:?} (tip: {})",
+            stream.stream_id,
+            &ranking[..stream.rank_depth as usize],
+            stream.tip_amount
+        );
+
+        emit!(RankingResolved {
+            stream: stream.key(),
+            ranking,
+            tip_amount: stream.tip_amount
+        });
+
+        Ok(())
+    }
+
+    /// Create the parlay pool for a stream. Parlay stakes are escrowed in the stream vault
+    /// (outside the stream's own pool totals) and winners split the pool.
+    pub fn initialize_parlay_pool(ctx: Context<InitializeParlayPool>) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        let pool = &mut ctx.accounts.parlay_pool;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+
+        pool.stream = stream.key();
+        pool.total_staked = 0;
+        pool.winning_stake = 0;
+        pool.parlay_count = 0;
+        pool.settled_count = 0;
+        pool.bump = ctx.bumps.parlay_pool;
+
+        msg!("Parlay pool initialized for stream {}", stream.stream_id);
+        Ok(())
+    }
+
+    /// Place a parlay: one stake across 2..=MAX_PARLAY_LEGS legs, each a (stream, choice).
+    /// The leg streams must be passed as remaining accounts in leg order, followed by the
+    /// bettor's access passes and participants for fee-charging and gated legs. Parlays
+    /// close at the pool stream's lock and every leg must still be open.
+    pub fn place_parlay<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceParlay<'info>>,
+        parlay_id: u64,
+        legs: Vec<ParlayLeg>,
+        stake_amount: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let home = &ctx.accounts.stream;
+
+        require!(stake_amount > 0, CypherCastError::InvalidStakeAmount);
+        require!(
+            (2..=MAX_PARLAY_LEGS).contains(&legs.len()),
+            CypherCastError::InvalidParlay
+        );
+        require!(
+            legs.len() <= ctx.remaining_accounts.len(),
+            CypherCastError::InvalidParlay
+        );
+        home.require_open_for_predictions(now)?;
+
+        // Leg streams come first, then for each leg in order the bettor's access pass if it
+        // charges an entry fee and the bettor's `Participant` if it is gated
+        let (leg_infos, extra_infos) = ctx.remaining_accounts.split_at(legs.len());
+        let mut extras = extra_infos.iter();
+        let mut parlay_legs = [ParlayLeg::default(); MAX_PARLAY_LEGS];
+        for (i, (leg, info)) in legs.iter().zip(leg_infos.iter()).enumerate() {
+            require!(leg.stream == info.key(), CypherCastError::InvalidParlay);
+            require!(
+                !legs[..i].iter().any(|l| l.stream == leg.stream),
+                CypherCastError::InvalidParlay
+            );
+            require!(leg.choice <= MAX_CHOICES, CypherCastError::InvalidChoice);
+
+            let leg_stream = Account::<Stream>::try_from(info)?;
+            require!(leg_stream.is_active, CypherCastError::StreamNotActive);
+            require!(leg_stream.canceled_at == 0, CypherCastError::Canceled);
+            require!(
+                leg_stream.resolves_to_choice(),
+                CypherCastError::UnsupportedMarketMode
+            );
+            leg_stream.require_open_for_predictions(now)?;
+            if leg_stream.entry_fee > 0 {
+                let info = extras.next().ok_or(CypherCastError::AccessPassRequired)?;
+                let pass = Account::<AccessPass>::try_from(info)?;
+                require!(
+                    pass.stream == leg.stream,
+                    CypherCastError::AccessPassRequired
+                );
+                require_access_pass(&leg_stream, ctx.accounts.bettor.key, Some(&pass))?;
+            }
+            if leg_stream.gate != StreamGate::None {
+                let info = extras.next().ok_or(CypherCastError::GateNotSatisfied)?;
+                let participant = Account::<Participant>::try_from(info)?;
+                require!(
+                    participant.stream == leg.stream
+                        && participant.viewer == *ctx.accounts.bettor.key,
+                    CypherCastError::GateNotSatisfied
+                );
+                require_gate_passed(&leg_stream, Some(&participant))?;
+            }
+            parlay_legs[i] = *leg;
+        }
+        require!(extras.next().is_none(), CypherCastError::InvalidParlay);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.bettor_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.bettor.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, stake_amount)?;
+
+        let pool = &mut ctx.accounts.parlay_pool;
+        pool.total_staked = pool
+            .total_staked
+            .checked_add(stake_amount)
+            .ok_or(CypherCastError::Overflow)?;
+        pool.parlay_count = pool
+            .parlay_count
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+
+        let parlay = &mut ctx.accounts.parlay;
+        parlay.pool = pool.key();
+        parlay.bettor = *ctx.accounts.bettor.key;
+        parlay.parlay_id = parlay_id;
+        parlay.legs = parlay_legs;
+        parlay.leg_count = legs.len() as u8;
+        parlay.stake_amount = stake_amount;
+        parlay.status = ParlayStatus::Open;
+        parlay.claimed = false;
+        parlay.bump = ctx.bumps.parlay;
+
+        emit!(ParlayPlaced {
+            pool: pool.key(),
+            parlay: parlay.key(),
+            bettor: parlay.bettor,
+            leg_count: parlay.leg_count,
+            amount: stake_amount
+        });
+
+        Ok(())
+    }
+
+    /// Settle a parlay once its legs are final (permissionless; leg streams as remaining
+    /// accounts in leg order). Any canceled or voided leg voids the parlay; otherwise it
+    /// wins only if every leg resolved to the chosen outcome.
+    pub fn settle_parlay<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleParlay<'info>>,
+    ) -> Result<()> {
+        let parlay = &mut ctx.accounts.parlay;
+        let pool = &mut ctx.accounts.parlay_pool;
+        let leg_count = parlay.leg_count as usize;
+
+        require!(
+            parlay.status == ParlayStatus::Open,
+            CypherCastError::ParlayAlreadySettled
+        );
+        require!(
+            ctx.remaining_accounts.len() == leg_count,
+            CypherCastError::InvalidParlay
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut status = ParlayStatus::Won;
+        for (leg, info) in parlay.legs[..leg_count]
+            .iter()
+            .zip(ctx.remaining_accounts.iter())
+        {
+            require!(leg.stream == info.key(), CypherCastError::InvalidParlay);
+            let leg_stream = Account::<Stream>::try_from(info)?;
+            // A leg left unresolved past the grace period can never settle; void instead of
+            // holding the whole pool hostage
+            let abandoned = !leg_stream.is_resolved
+                && now
+                    >= leg_stream
+                        .lock_time()?
+                        .saturating_add(PARLAY_SETTLEMENT_GRACE_SECS);
+            if leg_stream.is_refund_mode() || abandoned {
+                status = ParlayStatus::Void;
+                break;
+            }
+            require!(leg_stream.is_resolved, CypherCastError::NotResolved);
+            if leg_stream.winner_weights[leg.choice as usize] == 0 {
+                status = ParlayStatus::Lost;
+            }
+        }
+
+        match status {
+            ParlayStatus::Won => {
+                pool.winning_stake = pool
+                    .winning_stake
+                    .checked_add(parlay.stake_amount)
+                    .ok_or(CypherCastError::Overflow)?;
+            }
+            // Void stakes leave the pot and are refunded in full
+            ParlayStatus::Void => {
+                pool.total_staked = pool
+                    .total_staked
+                    .checked_sub(parlay.stake_amount)
+                    .ok_or(CypherCastError::Overflow)?;
+            }
+            _ => {}
+        }
+        pool.settled_count = pool
+            .settled_count
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+        parlay.status = status;
+
+        emit!(ParlaySettled {
+            pool: pool.key(),
+            parlay: parlay.key(),
+            status
+        });
+
+        Ok(())
+    }
+
+    /// Claim a settled parlay. Void parlays refund the stake immediately. Once every parlay
+    /// in the pool is settled and the pool stream is locked, winners split the pool pro rata
+    /// to stake; if no parlay won, every stake is refunded.
+    pub fn claim_parlay(ctx: Context<ClaimParlay>) -> Result<()> {
+        let parlay = &mut ctx.accounts.parlay;
+        let pool = &ctx.accounts.parlay_pool;
+        let stream = &ctx.accounts.stream;
+
+        require!(!parlay.claimed, CypherCastError::RewardAlreadyClaimed);
+
+        let amount = match parlay.status {
+            ParlayStatus::Open => return err!(CypherCastError::NotResolved),
+            ParlayStatus::Void => parlay.stake_amount,
+            ParlayStatus::Won | ParlayStatus::Lost => {
+                let now = Clock::get()?.unix_timestamp;
+                require!(
+                    now >= stream.lock_time()?,
+                    CypherCastError::StreamStillActive
+                );
+                require!(
+                    pool.settled_count == pool.parlay_count,
+                    CypherCastError::TallyIncomplete
+                );
+                if pool.winning_stake == 0 {
+                    parlay.stake_amount
+                } else {
+                    require!(
+                        parlay.status == ParlayStatus::Won,
+                        CypherCastError::NotWinner
+                    );
+                    pro_rata(
+                        pool.total_staked,
+                        parlay.stake_amount,
+                        pool.winning_stake as u128,
+                    )?
+                }
+            }
+        };
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.bettor_token_account,
+            amount,
+        )?;
+        parlay.claimed = true;
+
+        msg!("Parlay {} claimed: {} tokens", parlay.parlay_id, amount);
+
+        emit!(RewardClaimed {
+            stream: stream.key(),
+            viewer: parlay.bettor,
+            amount
+        });
+
+        Ok(())
+    }
+
+    /// Seed a choice with liquidity while the stream is in Draft so the first viewers see
+    /// real odds. Anyone (creator or sponsor) may seed. The seed counts toward the pool
+    /// totals like a stake: if the choice wins the seeder claims its proportional share,
+    /// otherwise it funds the winners.
+    pub fn seed_liquidity(ctx: Context<SeedLiquidity>, choice: u8, amount: u64) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let vault = &mut ctx.accounts.vault;
+        let seed = &mut ctx.accounts.liquidity_seed;
+
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(
+            stream.market_mode == MarketMode::Parimutuel,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(amount > 0, CypherCastError::InvalidStakeAmount);
+        require!(choice <= MAX_CHOICES, CypherCastError::InvalidChoice);
+        require_allowlisted(stream, ctx.accounts.participant.as_deref())?;
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.seeder_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.seeder.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        vault.total_deposited = vault
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.total_stake = stream
+            .total_stake
+            .checked_add(amount)
+            .ok_or(CypherCastError::Overflow)?;
+        let idx = choice as usize;
+        stream.total_by_choice[idx] = stream.total_by_choice[idx]
+            .checked_add(amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.seeded_by_choice[idx] = stream.seeded_by_choice[idx]
+            .checked_add(amount)
+            .ok_or(CypherCastError::Overflow)?;
+
+        seed.stream = stream.key();
+        seed.seeder = *ctx.accounts.seeder.key;
+        seed.choice = choice;
+        seed.amount = amount;
+        seed.claimed = false;
+        seed.bump = ctx.bumps.liquidity_seed;
+
+        emit!(LiquiditySeeded {
+            stream: stream.key(),
+            seeder: seed.seeder,
+            choice,
+            amount
+        });
+
+        Ok(())
+    }
+
+    /// Settle a liquidity seed: its proportional share if the seeded choice won, or the
+    /// full amount back if the stream was canceled or voided.
+    pub fn claim_seed(ctx: Context<ClaimSeed>) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        let seed = &mut ctx.accounts.liquidity_seed;
+
+        require!(!seed.claimed, CypherCastError::RewardAlreadyClaimed);
+
+        let amount = if stream.is_refund_mode() {
+            seed.amount
+        } else {
+            require!(stream.is_resolved, CypherCastError::NotResolved);
+            require!(
+                stream.winner_weights[seed.choice as usize] > 0,
+                CypherCastError::NotWinner
+            );
+            let distributable = stream.distributable(ctx.accounts.vault.total_deposited)?;
+            stream.choice_payout(distributable, seed.choice, seed.amount)?
+        };
+
+        release_from_vault(
+            &ctx.accounts.token_program,
+            &mut ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.seeder_token_account,
+            amount,
+        )?;
+        seed.claimed = true;
+
+        msg!(
+            "Liquidity seed on choice {} returned {} tokens to {}",
+            seed.choice,
+            amount,
+            seed.seeder
+        );
+        Ok(())
+    }
+
+    /// Add a sponsor-funded bonus that is paid to winners on top of the staked pool.
+    /// The bonus sits in the stream vault but stays out of `total_deposited`, so the
+    /// creator's tip is never taken from it.
+    pub fn sponsor_stream(
+        ctx: Context<SponsorStream>,
+        amount: u64,
+        metadata_uri_hash: [u8; 32],
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let sponsorship = &mut ctx.accounts.sponsorship;
+
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+        require!(
+            !stream.is_house_backed(),
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(amount > 0, CypherCastError::InvalidStakeAmount);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.sponsor_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.sponsor.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, amount)?;
+
+        stream.bonus_pool = stream
+            .bonus_pool
+            .checked_add(amount)
+            .ok_or(CypherCastError::Overflow)?;
+
+        sponsorship.stream = stream.key();
+        sponsorship.sponsor = *ctx.accounts.sponsor.key;
+        sponsorship.index = stream.sponsorship_count;
+        sponsorship.amount = amount;
+        sponsorship.metadata_uri_hash = metadata_uri_hash;
+        sponsorship.created_at = Clock::get()?.unix_timestamp;
+        sponsorship.reclaimed = false;
+        sponsorship.bump = ctx.bumps.sponsorship;
+
+        stream.sponsorship_count = stream
+            .sponsorship_count
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+
+        emit!(SponsorshipAdded {
+            stream: stream.key(),
+            sponsor: sponsorship.sponsor,
+            index: sponsorship.index,
+            amount,
+            metadata_uri_hash
+        });
+
+        Ok(())
+    }
+
+    /// Return a sponsorship to its sponsor when the stream is canceled or voided, or
+    /// resolved with nobody to pay the bonus to.
+    pub fn reclaim_sponsorship(ctx: Context<ReclaimSponsorship>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let sponsorship = &mut ctx.accounts.sponsorship;
+
+        require!(
+            stream.is_refund_mode() || (stream.is_resolved && stream.has_no_winners()),
+            CypherCastError::SponsorshipInUse
+        );
+        require!(
+            !sponsorship.reclaimed,
+            CypherCastError::RefundAlreadyClaimed
+        );
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.sponsor_token_account,
+            sponsorship.amount,
+        )?;
+
+        stream.bonus_pool = stream
+            .bonus_pool
+            .checked_sub(sponsorship.amount)
+            .ok_or(CypherCastError::Overflow)?;
+        sponsorship.reclaimed = true;
+
+        msg!(
+            "Sponsorship of {} tokens returned to {}",
+            sponsorship.amount,
+            sponsorship.sponsor
+        );
+        Ok(())
+    }
+
+    /// Switch a Draft stream to free-to-play: predictions carry no stake and winners split
+    /// the sponsor-funded `bonus_pool`, equally or by entry weight.
+    pub fn configure_free_to_play(
+        ctx: Context<ConfigureFreeToPlay>,
+        split: FreePlaySplit,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(stream.total_stake == 0, CypherCastError::InvalidConfig);
+
+        stream.market_mode = MarketMode::FreeToPlay;
+        stream.free_play_split = split;
+
+        msg!(
+            "Stream {} configured as free-to-play ({:?} split)",
+            stream.stream_id,
+            split
+        );
+        Ok(())
+    }
+
+    /// Set the anti-sybil gate viewers must pass to enter a Draft stream.
+    pub fn set_stream_gate(ctx: Context<ConfigureStream>, gate: StreamGate) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        gate.validate()?;
+
+        stream.gate = gate;
+
+        msg!("Stream {} gate set to {:?}", stream.stream_id, gate);
+        Ok(())
+    }
+
+    /// Enter a free-to-play stream. Requires a `Participant` record and passing the
+    /// stream's gate. With `EntryWeight` splits, earlier entries weigh more (seconds left
+    /// before lock).
+    pub fn submit_free_prediction(ctx: Context<SubmitFreePrediction>, choice: u8) -> Result<()> {
+        let prediction = &mut ctx.accounts.prediction;
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.market_mode == MarketMode::FreeToPlay,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(stream.is_active, CypherCastError::StreamNotActive);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(choice <= MAX_CHOICES, CypherCastError::InvalidChoice);
+        let now = Clock::get()?.unix_timestamp;
+        stream.require_open_for_predictions(now)?;
+        let lock_time = stream.lock_time()?;
+
+        verify_gate(
+            stream,
+            ctx.accounts.viewer.key,
+            ctx.accounts.gate_token_account.as_ref(),
+            ctx.accounts.gate_metadata.as_ref(),
+            ctx.accounts.instructions.as_ref(),
+        )?;
+
+        let weight = match stream.free_play_split {
+            FreePlaySplit::Equal => 1,
+            FreePlaySplit::EntryWeight => (lock_time - now) as u64,
+        };
+
+        let idx = choice as usize;
+        stream.total_by_choice[idx] = stream.total_by_choice[idx]
+            .checked_add(weight)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.prediction_count = stream
+            .prediction_count
+            .checked_add(1)
+            .ok_or(CypherCastError::Overflow)?;
+
+        prediction.stream = stream.key();
+        prediction.viewer = *ctx.accounts.viewer.key;
+        prediction.choice = choice;
+        prediction.stake_amount = 0;
+        prediction.timestamp = now;
+        prediction.reward_claimed = false;
+        prediction.scalar_value = 0;
+        prediction.ranking = [0; MAX_RANK_DEPTH];
+        prediction.weight = weight as u128;
+        prediction.tallied = false;
+        prediction.bump = ctx.bumps.prediction;
+
+        msg!(
+            "Free prediction submitted: choice {} with weight {} by {}",
+            choice,
+            weight,
+            prediction.viewer
+        );
+
+        emit!(FreePredictionSubmitted {
+            stream: stream.key(),
+            viewer: prediction.viewer,
+            choice,
+            weight
+        });
+
+        Ok(())
+    }
+
+    /// Charge viewers an entry fee in the vault mint when they join a Draft stream.
+    /// The fee is split by bps between the creator, the community vault and the prize pool.
+    pub fn configure_entry_fee(
+        ctx: Context<ConfigureEntryFee>,
+        entry_fee: u64,
+        split: EntryFeeSplit,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(
+            split.creator_bps as u32 + split.community_bps as u32 + split.prize_bps as u32
+                == 10_000,
+            CypherCastError::InvalidFeeSplit
+        );
+
+        stream.entry_fee = entry_fee;
+        stream.entry_fee_split = split;
+
+        msg!(
+            "Stream {} entry fee set to {} ({:?})",
+            stream.stream_id,
+            entry_fee,
+            split
+        );
+        Ok(())
+    }
+
+    /// Pay out the creator and community shares of collected entry fees once the stream is
+    /// resolved. The prize share already sits in `bonus_pool`, and goes back to the pass
+    /// holders through `refund_access_pass` if nobody wins. Permissionless.
+    pub fn distribute_entry_fees(ctx: Context<DistributeEntryFees>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(stream.is_resolved, CypherCastError::NotResolved);
+        require!(!stream.is_refund_mode(), CypherCastError::Canceled);
+        require!(
+            !stream.entry_fees_distributed,
+            CypherCastError::EntryFeesAlreadyDistributed
+        );
+
+        let creator_amount = pro_rata(
+            stream.entry_fees_collected,
+            stream.entry_fee_split.creator_bps,
+            10_000,
+        )?;
+        let community_amount = stream
+            .entry_fees_collected
+            .checked_sub(stream.entry_fee_prize)
+            .and_then(|v| v.checked_sub(creator_amount))
+            .ok_or(CypherCastError::Overflow)?;
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.creator_token_account,
+            creator_amount,
+        )?;
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.community_vault_token_account,
+            community_amount,
+        )?;
+
+        let community_vault = &mut ctx.accounts.community_vault;
+        community_vault.total_contributions = community_vault
+            .total_contributions
+            .checked_add(community_amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.entry_fees_distributed = true;
+
+        msg!(
+            "Entry fees distributed: {} to creator, {} to community",
+            creator_amount,
+            community_amount
+        );
+        Ok(())
+    }
+
+    /// Refund an access pass after the stream is canceled or voided, or its prize share once
+    /// the stream resolves with nobody to pay it to. Permissionless, so the creator or an
+    /// indexer can crank refunds for every pass.
+    pub fn refund_access_pass(ctx: Context<RefundAccessPass>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let access_pass = &mut ctx.accounts.access_pass;
+
+        require!(
+            stream.is_refund_mode() || (stream.is_resolved && stream.has_no_winners()),
+            CypherCastError::Canceled
+        );
+        require!(!access_pass.refunded, CypherCastError::RefundAlreadyClaimed);
+
+        // Without winners the creator and community shares are still paid out as usual
+        let amount = if stream.is_refund_mode() {
+            access_pass.amount_paid
+        } else {
+            access_pass.prize_amount
+        };
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.viewer_token_account,
+            amount,
+        )?;
+
+        stream.bonus_pool = stream
+            .bonus_pool
+            .checked_sub(access_pass.prize_amount)
+            .ok_or(CypherCastError::Overflow)?;
+        access_pass.refunded = true;
+
+        msg!(
+            "Entry fee of {} tokens refunded to {}",
+            amount,
+            access_pass.viewer
+        );
+        Ok(())
+    }
+
+    /// Make a Draft stream invite-only. `root` is the Merkle root of `(viewer, stake_cap)`
+    /// leaves built by the client's `AllowlistTree`; all zeroes clears the allowlist.
+    /// Viewers who joined under a different root may not stake.
+    pub fn set_allowlist(ctx: Context<ConfigureStream>, root: [u8; 32]) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+
+        stream.allowlist_root = root;
+
+        msg!("Stream {} allowlist root updated", stream.stream_id);
+        Ok(())
+    }
+
+    /// Set anti-whale limits on a Draft stream (0 disables each limit): a minimum stake
+    /// per submission, a cap on each viewer's cumulative stake, and a cap on the share of
+    /// any choice's pool a single viewer may hold once that pool reaches
+    /// `share_limit_min_pool`. Pools that cannot be seeded need that floor for a share cap.
+    pub fn configure_stake_limits(
+        ctx: Context<ConfigureStream>,
+        min_stake: u64,
+        max_stake_per_viewer: u64,
+        max_share_bps_of_choice: u16,
+        share_limit_min_pool: u64,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+
+        stream.min_stake = min_stake;
+        stream.max_stake_per_viewer = max_stake_per_viewer;
+        stream.max_share_bps_of_choice = max_share_bps_of_choice;
+        stream.share_limit_min_pool = share_limit_min_pool;
+        stream.validate_stake_limits()?;
+
+        msg!(
+            "Stream {} stake limits: min {}, per viewer {}, share {} bps above {}",
+            stream.stream_id,
+            min_stake,
+            max_stake_per_viewer,
+            max_share_bps_of_choice,
+            share_limit_min_pool
+        );
+        Ok(())
+    }
+
+    /// Require a minimum number of predictions and total stake, and optionally stakes on at
+    /// least two sides, before a Draft stream can be settled. Checked by `finalize_lock`.
+    pub fn configure_participation_threshold(
+        ctx: Context<ConfigureStream>,
+        min_participants: u64,
+        min_total_stake: u64,
+        require_two_sided: bool,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+
+        stream.min_participants = min_participants;
+        stream.min_total_stake = min_total_stake;
+        stream.require_two_sided = require_two_sided;
+
+        msg!(
+            "Stream {} threshold: {} predictions, {} stake, two-sided {}",
+            stream.stream_id,
+            min_participants,
+            min_total_stake,
+            require_two_sided
+        );
+        Ok(())
+    }
+
+    /// Check participation thresholds once the stream locks. Permissionless. If a threshold
+    /// is missed the stream is canceled, so `claim_refund` opens and no tip is ever taken.
+    pub fn finalize_lock(ctx: Context<FinalizeLock>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(stream.canceled_at == 0, CypherCastError::AlreadyCanceled);
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+        require!(
+            !stream.lock_finalized,
+            CypherCastError::LockAlreadyFinalized
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= stream.lock_time()?, CypherCastError::StreamNotLocked);
+
+        let two_sided = if stream.resolves_to_choice() {
+            stream.backed_choice_count() >= 2
+        } else {
+            stream.prediction_count >= 2
+        };
+        let threshold_met = stream.prediction_count >= stream.min_participants
+            && stream.viewer_stake() >= stream.min_total_stake
+            && (!stream.require_two_sided || two_sided);
+
+        if threshold_met {
+            stream.lock_finalized = true;
+        } else {
+            stream.is_active = false;
+            stream.canceled_at = now;
+        }
+
+        emit!(LockFinalized {
+            stream: stream.key(),
+            prediction_count: stream.prediction_count,
+            total_stake: stream.total_stake,
+            threshold_met
+        });
+
+        msg!(
+            "Stream {} lock finalized (threshold met: {})",
+            stream.stream_id,
+            threshold_met
+        );
+        Ok(())
+    }
+
+    /// Create the roles account that lists a stream's moderators.
+    pub fn initialize_stream_roles(ctx: Context<InitializeStreamRoles>) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        let roles = &mut ctx.accounts.stream_roles;
+
+        require!(
+            stream.owner == *ctx.accounts.owner.key,
+            CypherCastError::Unauthorized
+        );
+
+        roles.stream = stream.key();
+        roles.moderators = Vec::new();
+        roles.resolvers = Vec::new();
+        roles.pending_owner = Pubkey::default();
+        roles.bump = ctx.bumps.stream_roles;
+        Ok(())
+    }
+
+    /// Grant `role` to `member`. Moderators may lock, pause, resume and end the stream;
+    /// resolvers may resolve it.
+    pub fn grant_role(
+        ctx: Context<ManageStreamRoles>,
+        role: StreamRole,
+        member: Pubkey,
+    ) -> Result<()> {
+        let roles = &mut ctx.accounts.stream_roles;
+
+        require!(
+            ctx.accounts.stream.owner == *ctx.accounts.owner.key,
+            CypherCastError::Unauthorized
+        );
+        let members = roles.members_mut(role);
+        require!(!members.contains(&member), CypherCastError::InvalidConfig);
+        require!(
+            members.len() < StreamRoles::MAX_MEMBERS,
+            CypherCastError::TooManyRoleMembers
+        );
+
+        members.push(member);
+
+        emit!(RoleGranted {
+            stream: ctx.accounts.stream.key(),
+            role,
+            member
+        });
+
+        Ok(())
+    }
+
+    pub fn revoke_role(
+        ctx: Context<ManageStreamRoles>,
+        role: StreamRole,
+        member: Pubkey,
+    ) -> Result<()> {
+        let roles = &mut ctx.accounts.stream_roles;
+
+        require!(
+            ctx.accounts.stream.owner == *ctx.accounts.owner.key,
+            CypherCastError::Unauthorized
+        );
+        let members = roles.members_mut(role);
+        let index = members
+            .iter()
+            .position(|m| *m == member)
+            .ok_or(CypherCastError::InvalidConfig)?;
+
+        members.swap_remove(index);
+
+        emit!(RoleRevoked {
+            stream: ctx.accounts.stream.key(),
+            role,
+            member
+        });
+
+        Ok(())
+    }
+
+    /// Nominate `new_owner`, who takes over once they call `accept_ownership`. Nominating
+    /// the default key withdraws a pending transfer.
+    pub fn transfer_ownership(ctx: Context<ManageStreamRoles>, new_owner: Pubkey) -> Result<()> {
+        let roles = &mut ctx.accounts.stream_roles;
+
+        require!(
+            ctx.accounts.stream.owner == *ctx.accounts.owner.key,
+            CypherCastError::Unauthorized
+        );
+        require!(
+            new_owner != ctx.accounts.stream.owner,
+            CypherCastError::InvalidConfig
+        );
+
+        roles.pending_owner = new_owner;
+
+        msg!(
+            "Stream {} ownership offered to {}",
+            ctx.accounts.stream.stream_id,
+            new_owner
+        );
+        Ok(())
+    }
+
+    /// Complete a two-step ownership transfer, signed by the nominated owner.
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let roles = &mut ctx.accounts.stream_roles;
+
+        require!(
+            roles.pending_owner != Pubkey::default()
+                && roles.pending_owner == *ctx.accounts.new_owner.key,
+            CypherCastError::Unauthorized
+        );
+
+        let previous_owner = stream.owner;
+        stream.owner = roles.pending_owner;
+        roles.pending_owner = Pubkey::default();
+
+        emit!(OwnershipTransferred {
+            stream: stream.key(),
+            previous_owner,
+            new_owner: stream.owner
+        });
+
+        Ok(())
+    }
+
+    /// Lock predictions now, when the live moment actually starts. The stamped `locked_at`
+    /// replaces the scheduled lock if it is earlier.
+    pub fn lock_stream(ctx: Context<ModerateStream>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require_role(
+            stream,
+            ctx.accounts.stream_roles.as_deref(),
+            ctx.accounts.authority.key,
+            StreamRole::Moderator,
+        )?;
+        require!(stream.is_active, CypherCastError::StreamNotActive);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < stream.lock_time()?, CypherCastError::StreamLocked);
+
+        stream.locked_at = now;
+
+        emit!(PredictionsLocked {
+            stream: stream.key(),
+            authority: *ctx.accounts.authority.key,
+            locked_at: now
+        });
+
+        msg!("Stream {} locked at {}", stream.stream_id, now);
+        Ok(())
+    }
+
+    /// Temporarily stop accepting predictions.
+    pub fn pause_predictions(ctx: Context<ModerateStream>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require_role(
+            stream,
+            ctx.accounts.stream_roles.as_deref(),
+            ctx.accounts.authority.key,
+            StreamRole::Moderator,
+        )?;
+        require!(stream.is_active, CypherCastError::StreamNotActive);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(!stream.is_paused, CypherCastError::PredictionsPaused);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < stream.lock_time()?, CypherCastError::StreamLocked);
+
+        stream.is_paused = true;
+
+        emit!(PredictionsPaused {
+            stream: stream.key(),
+            authority: *ctx.accounts.authority.key,
+            timestamp: now
+        });
+
+        msg!("Predictions paused on stream {}", stream.stream_id);
+        Ok(())
+    }
+
+    pub fn resume_predictions(ctx: Context<ModerateStream>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require_role(
+            stream,
+            ctx.accounts.stream_roles.as_deref(),
+            ctx.accounts.authority.key,
+            StreamRole::Moderator,
+        )?;
+        require!(stream.is_active, CypherCastError::StreamNotActive);
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+        require!(stream.is_paused, CypherCastError::PredictionsNotPaused);
+
+        stream.is_paused = false;
+
+        emit!(PredictionsResumed {
+            stream: stream.key(),
+            authority: *ctx.accounts.authority.key,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+
+        msg!("Predictions resumed on stream {}", stream.stream_id);
+        Ok(())
+    }
+
+    /// Limit how far the lock may be extended after activation, and how long existing
+    /// predictors get to withdraw after each extension.
+    pub fn configure_reschedule_policy(
+        ctx: Context<ConfigureStream>,
+        max_lock_extension_secs: i64,
+        withdrawal_window_secs: i64,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(
+            max_lock_extension_secs >= 0 && withdrawal_window_secs >= 0,
+            CypherCastError::InvalidConfig
+        );
+
+        stream.max_lock_extension_secs = max_lock_extension_secs;
+        stream.withdrawal_window_secs = withdrawal_window_secs;
+
+        msg!(
+            "Stream {} reschedule policy: extend up to {}s, {}s withdrawal window",
+            stream.stream_id,
+            max_lock_extension_secs,
+            withdrawal_window_secs
+        );
+        Ok(())
+    }
+
+    /// Require the outcome to be observed for at least `min_observation_secs` after the lock
+    /// before the stream can be resolved.
+    pub fn configure_observation_window(
+        ctx: Context<ConfigureStream>,
+        min_observation_secs: i64,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        validation::validate_observation_window(min_observation_secs)?;
+
+        stream.min_observation_secs = min_observation_secs;
+
+        msg!(
+            "Stream {} observation window: {}s after lock",
+            stream.stream_id,
+            min_observation_secs
+        );
+        Ok(())
+    }
+
+    /// Change a stream's timing. In Draft every field may change. After activation only
+    /// the lock may move later, by at most `max_lock_extension_secs` in total, and each
+    /// extension opens a fee-free withdrawal window for existing predictors.
+    pub fn reschedule_stream(
+        ctx: Context<RescheduleStream>,
+        start_time: i64,
+        lock_offset_secs: i64,
+        grace_period_secs: i64,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+
+        let now = Clock::get()?.unix_timestamp;
+        let old_start_time = stream.start_time;
+        let old_lock_time = stream.lock_time()?;
+
+        if stream.is_draft() {
+            validation::validate_schedule(now, start_time, lock_offset_secs, grace_period_secs)?;
+            stream.start_time = start_time;
+            stream.lock_offset_secs = lock_offset_secs;
+            stream.grace_period_secs = grace_period_secs;
+        } else {
+            // The frozen config stays intact; extensions are tracked separately
+            require!(
+                start_time == stream.start_time && grace_period_secs == stream.grace_period_secs,
+                CypherCastError::AlreadyActivated
+            );
+            require!(
+                stream.locked_at == 0 && now < old_lock_time,
+                CypherCastError::StreamLocked
+            );
+            validation::validate_lock_offset(now, start_time, lock_offset_secs)?;
+            let extension = lock_offset_secs
+                .checked_sub(stream.lock_offset_secs)
+                .ok_or(CypherCastError::Overflow)?;
+            require!(
+                extension > stream.lock_extension_secs,
+                CypherCastError::InvalidConfig
+            );
+            require!(
+                extension <= stream.max_lock_extension_secs,
+                CypherCastError::LockExtensionTooLong
+            );
+
+            stream.lock_extension_secs = extension;
+            stream.rescheduled_at = now;
+            // The window never outlives the (new) lock
+            stream.withdrawal_deadline = now
+                .checked_add(stream.withdrawal_window_secs)
+                .ok_or(CypherCastError::Overflow)?
+                .min(stream.lock_time()?);
+        }
+
+        emit!(StreamRescheduled {
+            stream: stream.key(),
+            old_start_time,
+            new_start_time: stream.start_time,
+            old_lock_time,
+            new_lock_time: stream.lock_time()?,
+            withdrawal_deadline: stream.withdrawal_deadline
+        });
+
+        msg!(
+            "Stream {} rescheduled: lock at {}",
+            stream.stream_id,
+            stream.lock_time()?
+        );
+        Ok(())
+    }
+
+    /// Withdraw a prediction in full, without fee, during the window opened by a lock
+    /// extension. Only predictions placed before the extension qualify. The prediction is
+    /// closed so the viewer may predict again.
+    pub fn withdraw_prediction(ctx: Context<WithdrawPrediction>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let prediction = &ctx.accounts.prediction;
+
+        require!(stream.canceled_at == 0, CypherCastError::Canceled);
+        require!(!stream.is_resolved, CypherCastError::AlreadyResolved);
+        require!(
+            matches!(
+                stream.market_mode,
+                MarketMode::Parimutuel
+                    | MarketMode::FixedOdds
+                    | MarketMode::Scalar
+                    | MarketMode::Ranked
+            ),
+            CypherCastError::UnsupportedMarketMode
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now < stream.withdrawal_deadline
+                && now < stream.lock_time()?
+                && prediction.timestamp <= stream.rescheduled_at,
+            CypherCastError::WithdrawalWindowClosed
+        );
+        // The stake was recorded on the participant whenever the stream tracks viewers
+        require!(
+            ctx.accounts.participant.is_some() || !stream.tracks_participants(),
+            CypherCastError::ParticipantRequired
+        );
+
+        let amount = prediction.stake_amount;
+        let idx = prediction.choice as usize;
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.viewer_token_account,
+            amount,
+        )?;
+
+        // Remove the stake from the pool as if it was never placed
+        let vault = &mut ctx.accounts.vault;
+        vault.total_deposited = vault
+            .total_deposited
+            .checked_sub(amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.total_stake = stream
+            .total_stake
+            .checked_sub(amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.prediction_count = stream
+            .prediction_count
+            .checked_sub(1)
+            .ok_or(CypherCastError::Overflow)?;
+        if stream.resolves_to_choice() {
+            stream.total_by_choice[idx] = stream.total_by_choice[idx]
+                .checked_sub(amount)
+                .ok_or(CypherCastError::Overflow)?;
+        }
+        if stream.market_mode == MarketMode::FixedOdds {
+            let payout = stream.fixed_odds_payout(prediction.choice, amount)?;
+            stream.liability_by_choice[idx] = stream.liability_by_choice[idx]
+                .checked_sub(payout)
+                .ok_or(CypherCastError::Overflow)?;
+            // The withdrawn stake was also backing every other choice's payouts
+            let capacity = stream
+                .house_reserve
+                .checked_add(stream.total_stake)
+                .ok_or(CypherCastError::Overflow)?;
+            require!(
+                stream.liability_by_choice.iter().all(|&l| l <= capacity),
+                CypherCastError::ReserveExceeded
+            );
+        }
+        if let Some(participant) = ctx.accounts.participant.as_mut() {
+            participant.stake_amount = participant.stake_amount.saturating_sub(amount);
+            participant.stake_by_choice[idx] =
+                participant.stake_by_choice[idx].saturating_sub(amount);
+        }
+
+        msg!(
+            "Prediction of {} tokens withdrawn by {}",
+            amount,
+            prediction.viewer
+        );
+        Ok(())
+    }
+
+    /// Require the creator to post a bond before activation. A creator who cancels after
+    /// the lock forfeits it to `slash_target`; `arbiter` (default key = none) may cancel
+    /// at any time without a slash.
+    pub fn configure_creator_bond(
+        ctx: Context<ConfigureStream>,
+        bond_amount: u64,
+        slash_target: BondSlashTarget,
+        arbiter: Pubkey,
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(!stream.bond_posted, CypherCastError::BondAlreadyPosted);
+        require!(
+            !stream.is_house_backed() || bond_amount == 0,
+            CypherCastError::UnsupportedMarketMode
+        );
+
+        stream.bond_amount = bond_amount;
+        stream.bond_slash_target = slash_target;
+        stream.arbiter = arbiter;
+
+        msg!(
+            "Stream {} creator bond: {} tokens, arbiter {}",
+            stream.stream_id,
+            bond_amount,
+            arbiter
+        );
+        Ok(())
+    }
+
+    /// Deposit the configured bond into the stream vault. Held outside `total_deposited`.
+    pub fn post_bond(ctx: Context<PostBond>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        require!(stream.bond_amount > 0, CypherCastError::InvalidConfig);
+        require!(!stream.bond_posted, CypherCastError::BondAlreadyPosted);
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.creator_token_account.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, stream.bond_amount)?;
+
+        stream.bond_posted = true;
+        stream.bond_poster = *ctx.accounts.creator.key;
+
+        emit!(BondPosted {
+            stream: stream.key(),
+            owner: stream.owner,
+            amount: stream.bond_amount
+        });
+
+        Ok(())
+    }
+
+    /// Return the bond to the wallet that posted it once the stream is resolved or canceled
+    /// without a slash.
+    pub fn reclaim_bond(ctx: Context<ReclaimBond>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(
+            stream.is_resolved || stream.canceled_at != 0,
+            CypherCastError::NotResolved
+        );
+        require!(stream.bond_posted, CypherCastError::BondNotPosted);
+        require!(!stream.bond_slashed, CypherCastError::BondSlashed);
+        require!(!stream.bond_released, CypherCastError::BondAlreadyReleased);
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.poster_token_account,
+            stream.bond_amount,
+        )?;
+        stream.bond_released = true;
+
+        msg!(
+            "Bond of {} tokens returned to {}",
+            stream.bond_amount,
+            stream.bond_poster
+        );
+        Ok(())
+    }
+
+    /// Send a slashed bond to the community vault. Permissionless. Used when the slash
+    /// target is the community vault, or to sweep what predictors were not paid (rounding
+    /// dust, unclaimed shares, or the whole bond if no refundable stake was placed) once every
+    /// refund is done or `BOND_CLAIM_WINDOW_SECS` have passed since the cancellation.
+    pub fn distribute_slashed_bond(ctx: Context<DistributeSlashedBond>) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+
+        require!(stream.bond_slashed, CypherCastError::BondNotSlashed);
+        require!(!stream.bond_released, CypherCastError::BondAlreadyReleased);
+        let claim_deadline = stream
+            .canceled_at
+            .checked_add(BOND_CLAIM_WINDOW_SECS)
+            .ok_or(CypherCastError::Overflow)?;
+        require!(
+            stream.bond_slash_target == BondSlashTarget::CommunityVault
+                || stream.refunded_stake >= stream.bond_refundable_stake()
+                || Clock::get()?.unix_timestamp >= claim_deadline,
+            CypherCastError::RefundsOutstanding
+        );
+
+        // Predictor-targeted bonds only leave their undistributed remainder here
+        let amount = stream
+            .bond_amount
+            .checked_sub(stream.bond_distributed)
+            .ok_or(CypherCastError::Overflow)?;
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.vault_token_account,
+            &ctx.accounts.community_vault_token_account,
+            amount,
+        )?;
+
+        let community_vault = &mut ctx.accounts.community_vault;
+        community_vault.total_contributions = community_vault
+            .total_contributions
+            .checked_add(amount)
+            .ok_or(CypherCastError::Overflow)?;
+        stream.bond_distributed = stream.bond_amount;
+        stream.bond_released = true;
+
+        msg!(
+            "Slashed bond of {} tokens sent to the community vault",
+            amount
+        );
+        Ok(())
+    }
+
+    /// Create the caller's creator profile, required by `create_stream`, which takes stream
+    /// IDs from its counter starting at 0. The handle is set by `claim_handle`.
+    pub fn initialize_creator_profile(
+        ctx: Context<InitializeCreatorProfile>,
+        metadata_uri: String,
+    ) -> Result<()> {
+        let profile = &mut ctx.accounts.creator_profile;
+
+        require!(
+            metadata_uri.len() <= CreatorProfile::MAX_URI_LEN,
+            CypherCastError::UriTooLong
+        );
+
+        profile.creator = *ctx.accounts.creator.key;
+        profile.handle = String::new();
+        profile.metadata_uri = metadata_uri;
+        profile.next_stream_id = 0;
+        profile.total_streams = 0;
+        profile.streams_resolved = 0;
+        profile.streams_canceled = 0;
+        profile.tips_earned = 0;
+        profile.created_at = Clock::get()?.unix_timestamp;
+        profile.bump = ctx.bumps.creator_profile;
+
+        msg!("Creator profile created for {}", profile.creator);
+        Ok(())
+    }
+
+    pub fn update_creator_profile(
+        ctx: Context<UpdateCreatorProfile>,
+        metadata_uri: String,
+    ) -> Result<()> {
+        let profile = &mut ctx.accounts.creator_profile;
+
+        require!(
+            metadata_uri.len() <= CreatorProfile::MAX_URI_LEN,
+            CypherCastError::UriTooLong
+        );
+
+        profile.metadata_uri = metadata_uri;
+
+        msg!("Creator profile updated for {}", profile.creator);
+        Ok(())
+    }
+
+    /// Set up the program-wide config. Only the program's upgrade authority may call it;
+    /// `admin` then controls reserved handles.
+    pub fn initialize_global_config(ctx: Context<InitializeGlobalConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        config.admin = ctx.accounts.admin.key();
+        config.reserved_handles = Vec::new();
+        config.bump = ctx.bumps.global_config;
+
+        msg!("Global config initialized with admin {}", config.admin);
+        Ok(())
+    }
+
+    /// Block `handle` from being claimed. Existing claims are unaffected.
+    pub fn reserve_handle(ctx: Context<ManageGlobalConfig>, handle: String) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        let handle = validation::normalize_handle(&handle)?;
+
+        require!(
+            !config.reserved_handles.contains(&handle),
+            CypherCastError::InvalidConfig
+        );
+        require!(
+            config.reserved_handles.len() < GlobalConfig::MAX_RESERVED_HANDLES,
+            CypherCastError::TooManyReservedHandles
+        );
+
+        msg!("Handle {} reserved", handle);
+        config.reserved_handles.push(handle);
+        Ok(())
+    }
+
+    pub fn unreserve_handle(ctx: Context<ManageGlobalConfig>, handle: String) -> Result<()> {
+        let config = &mut ctx.accounts.global_config;
+        let handle = validation::normalize_handle(&handle)?;
+        let index = config
+            .reserved_handles
+            .iter()
+            .position(|h| *h == handle)
+            .ok_or(CypherCastError::InvalidConfig)?;
+
+        config.reserved_handles.swap_remove(index);
+
+        msg!("Handle {} released from reservation", handle);
+        Ok(())
+    }
+
+    /// Claim a unique handle for the caller's profile. `handle` must already be in
+    /// normalized form, since it seeds the record; a taken handle fails at `init`.
+    pub fn claim_handle(ctx: Context<ClaimHandle>, handle: String) -> Result<()> {
+        let profile = &mut ctx.accounts.creator_profile;
+        let record = &mut ctx.accounts.handle_record;
+
+        require!(
+            validation::normalize_handle(&handle)? == handle,
+            CypherCastError::InvalidHandle
+        );
+        require!(
+            !ctx.accounts
+                .global_config
+                .reserved_handles
+                .contains(&handle),
+            CypherCastError::HandleReserved
+        );
+        require!(profile.handle.is_empty(), CypherCastError::ProfileHasHandle);
+
+        record.handle = handle.clone();
+        record.profile = profile.key();
+        record.owner = profile.creator;
+        record.pending_owner = Pubkey::default();
+        record.claimed_at = Clock::get()?.unix_timestamp;
+        record.bump = ctx.bumps.handle_record;
+        profile.handle = handle;
+
+        emit!(HandleClaimed {
+            handle: record.handle.clone(),
+            profile: record.profile,
+            owner: record.owner
+        });
+
+        Ok(())
+    }
+
+    /// Give up a handle. The record is closed so anyone may claim it again.
+    pub fn release_handle(ctx: Context<ReleaseHandle>) -> Result<()> {
+        let profile = &mut ctx.accounts.creator_profile;
+
+        profile.handle = String::new();
+
+        msg!(
+            "Handle {} released by {}",
+            ctx.accounts.handle_record.handle,
+            profile.creator
+        );
+        Ok(())
+    }
+
+    /// Offer a handle to `new_owner`, who takes it over once they call `accept_handle`.
+    /// Offering to the default key withdraws a pending transfer.
+    pub fn transfer_handle(ctx: Context<TransferHandle>, new_owner: Pubkey) -> Result<()> {
+        let record = &mut ctx.accounts.handle_record;
+
+        require!(new_owner != record.owner, CypherCastError::InvalidConfig);
+
+        record.pending_owner = new_owner;
+
+        msg!("Handle {} offered to {}", record.handle, new_owner);
+        Ok(())
+    }
+
+    /// Complete a handle transfer, signed by the nominated creator. Their profile must not
+    /// hold a handle yet.
+    pub fn accept_handle(ctx: Context<AcceptHandle>) -> Result<()> {
+        let record = &mut ctx.accounts.handle_record;
+        let new_profile = &mut ctx.accounts.creator_profile;
+
+        require!(
+            record.pending_owner != Pubkey::default()
+                && record.pending_owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(
+            new_profile.handle.is_empty(),
+            CypherCastError::ProfileHasHandle
+        );
+
+        ctx.accounts.previous_profile.handle = String::new();
+        new_profile.handle = record.handle.clone();
+        record.profile = new_profile.key();
+        record.owner = new_profile.creator;
+        record.pending_owner = Pubkey::default();
+
+        emit!(HandleClaimed {
+            handle: record.handle.clone(),
+            profile: record.profile,
+            owner: record.owner
+        });
+
+        Ok(())
+    }
+
+    /// Attach descriptive metadata to a Draft stream. It stays editable until activation,
+    /// which folds it into the config hash.
+    pub fn initialize_stream_metadata(
+        ctx: Context<InitializeStreamMetadata>,
+        description: String,
+        category: StreamCategory,
+        tags: Vec<String>,
+        media_uri: String,
+        content_hash: [u8; 32],
+    ) -> Result<()> {
+        let stream = &mut ctx.accounts.stream;
+        let metadata = &mut ctx.accounts.stream_metadata;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        StreamMetadata::validate(&description, &tags, &media_uri)?;
+
+        metadata.stream = stream.key();
+        metadata.description = description;
+        metadata.category = category;
+        metadata.tags = tags;
+        metadata.media_uri = media_uri;
+        metadata.content_hash = content_hash;
+        metadata.bump = ctx.bumps.stream_metadata;
+        stream.has_metadata = true;
+
+        msg!("Stream {} metadata created", stream.stream_id);
+        Ok(())
+    }
+
+    /// Replace the metadata of a Draft stream, resizing the account to fit.
+    pub fn update_stream_metadata(
+        ctx: Context<UpdateStreamMetadata>,
+        description: String,
+        category: StreamCategory,
+        tags: Vec<String>,
+        media_uri: String,
+        content_hash: [u8; 32],
+    ) -> Result<()> {
+        let stream = &ctx.accounts.stream;
+        let metadata = &mut ctx.accounts.stream_metadata;
+
+        require!(
+            stream.owner == *ctx.accounts.creator.key,
+            CypherCastError::Unauthorized
+        );
+        require!(stream.is_draft(), CypherCastError::AlreadyActivated);
+        StreamMetadata::validate(&description, &tags, &media_uri)?;
+
+        metadata.description = description;
+        metadata.category = category;
+        metadata.tags = tags;
+        metadata.media_uri = media_uri;
+        metadata.content_hash = content_hash;
+
+        msg!("Stream {} metadata updated", stream.stream_id);
+        Ok(())
+    }
+}
+
+/// Pay the streamer tip (once) from the vault to the creator.
+/// `tip_amount = floor(vault.total_deposited * tip_bps / 10_000)`; house-backed modes pay
+/// the creator through the leftover reserve instead.
+fn pay_streamer_tip<'info>(
+    stream: &mut Stream,
+    token_program: &Program<'info, Token>,
+    vault: &mut Account<'info, TokenVault>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    creator_token_account: &Account<'info, TokenAccount>,
+) -> Result<()> {
+    if stream.is_house_backed() || stream.tip_amount != 0 || stream.tip_bps == 0 {
+        return Ok(());
+    }
+    let tip_amount = ((vault.total_deposited as u128).saturating_mul(stream.tip_bps as u128)
+        / 10_000u128) as u64;
+    if tip_amount > 0 {
+        release_from_vault(
+            token_program,
+            vault,
+            vault_token_account,
+            creator_token_account,
+            tip_amount,
+        )?;
+        stream.tip_amount = tip_amount;
+    }
+    Ok(())
+}
+
+/// Check a new stake of `amount` against the stream's anti-whale limits and record it on
+/// the viewer's `Participant`. `choice` is `None` for markets without a choice (scalar,
+/// ranked), where the share limit applies to the whole pool. Must run before the stream
+/// totals are updated. The share is measured against the pool including this stake once
+/// it reaches `share_limit_min_pool`; below that, or through seeded liquidity, an empty
+/// pool can take its first stakes.
+fn enforce_stake_limits(
+    stream: &Stream,
+    participant: Option<&mut Participant>,
+    choice: Option<u8>,
+    amount: u64,
+) -> Result<()> {
+    let pool = match choice {
+        Some(choice) => *stream
+            .total_by_choice
+            .get(choice as usize)
+            .ok_or(CypherCastError::InvalidChoice)?,
+        None => stream.total_stake,
+    };
+
+    let Some(participant) = record_viewer_stake(stream, participant, amount)? else {
+        return Ok(());
+    };
+
+    let viewer_stake = match choice {
+        Some(choice) => participant.stake_by_choice[choice as usize]
+            .checked_add(amount)
+            .ok_or(CypherCastError::Overflow)?,
+        None => participant.stake_amount,
+    };
+    require_choice_share(
+        stream,
+        viewer_stake,
+        pool.checked_add(amount).ok_or(CypherCastError::Overflow)?,
+    )?;
+
+    if let Some(choice) = choice {
+        participant.stake_by_choice[choice as usize] = viewer_stake;
+    }
+    Ok(())
+}
+
+/// Apply the per-stake minimum, the allowlist and the cumulative per-viewer caps to a stake
+/// of `amount`, and add it to the viewer's `Participant`. Returns the participant, if any.
+fn record_viewer_stake<'a>(
+    stream: &Stream,
+    participant: Option<&'a mut Participant>,
+    amount: u64,
+) -> Result<Option<&'a mut Participant>> {
+    require!(amount >= stream.min_stake, CypherCastError::BelowMinStake);
+    require_allowlisted(stream, participant.as_deref())?;
+    let Some(participant) = participant else {
+        require!(
+            stream.max_stake_per_viewer == 0 && stream.max_share_bps_of_choice == 0,
+            CypherCastError::ParticipantRequired
+        );
+        return Ok(None);
+    };
+
+    let staked = participant
+        .stake_amount
+        .checked_add(amount)
+        .ok_or(CypherCastError::Overflow)?;
+    require!(
+        participant.stake_cap == 0 || staked <= participant.stake_cap,
+        CypherCastError::AllowlistCapExceeded
+    );
+    require!(
+        stream.max_stake_per_viewer == 0 || staked <= stream.max_stake_per_viewer,
+        CypherCastError::ViewerStakeCapExceeded
+    );
+    participant.stake_amount = staked;
+    Ok(Some(participant))
+}
+
+/// Require a viewer's holding on a choice to stay within `max_share_bps_of_choice` of the
+/// choice's pool, both measured after the new stake, once the pool is deep enough.
+fn require_choice_share(stream: &Stream, held_after: u64, pool_after: u64) -> Result<()> {
+    require!(
+        validation::within_share_limit(
+            held_after,
+            pool_after,
+            stream.max_share_bps_of_choice,
+            stream.share_limit_min_pool,
+        ),
+        CypherCastError::ChoiceShareExceeded
+    );
+    Ok(())
+}
+
+/// On invite-only streams, require a `Participant` admitted under the current allowlist
+/// root. Viewers who joined before the root was set (or changed) are not admitted.
+fn require_allowlisted(stream: &Stream, participant: Option<&Participant>) -> Result<()> {
+    if stream.allowlist_root == [0u8; 32] {
+        return Ok(());
+    }
+    require!(
+        participant.is_some_and(|p| p.allowlist_root == stream.allowlist_root),
+        CypherCastError::NotAllowlisted
+    );
+    Ok(())
+}
+
+/// The creator and any moderator listed in the stream's roles may moderate predictions.
+fn require_role(
+    stream: &Stream,
+    roles: Option<&StreamRoles>,
+    authority: &Pubkey,
+    role: StreamRole,
+) -> Result<()> {
+    let is_member = roles.is_some_and(|roles| roles.members(role).contains(authority));
+    require!(
+        stream.owner == *authority || is_member,
+        CypherCastError::Unauthorized
+    );
+    Ok(())
+}
+
+/// Require a valid access pass for `viewer` when the stream charges an entry fee.
+fn require_access_pass(
+    stream: &Stream,
+    viewer: &Pubkey,
+    access_pass: Option<&Account<AccessPass>>,
+) -> Result<()> {
+    if stream.entry_fee == 0 {
+        return Ok(());
+    }
+    let pass = access_pass.ok_or(CypherCastError::AccessPassRequired)?;
+    require!(
+        pass.viewer == *viewer && !pass.refunded,
+        CypherCastError::AccessPassRequired
+    );
+    Ok(())
+}
+
+/// On gated streams, require the viewer's `Participant`. `join_stream` verified the gate
+/// when it created the record, and the gate cannot change after activation.
+fn require_gate_passed(stream: &Stream, participant: Option<&Participant>) -> Result<()> {
+    require!(
+        stream.gate == StreamGate::None || participant.is_some(),
+        CypherCastError::GateNotSatisfied
+    );
+    Ok(())
+}
+
+/// Check that `viewer` passes the stream's entry gate.
+/// `TokenBalance` needs a token account of the gate mint owned by the viewer; `Collection`
+/// needs the viewer's NFT token account plus its Metaplex metadata account; `Attestation`
+/// needs the instructions sysvar and an ed25519 verify instruction immediately before this
+/// one, signed by the gate signer over `stream || viewer`.
+fn verify_gate(
+    stream: &Account<Stream>,
+    viewer: &Pubkey,
+    gate_token_account: Option<&Account<TokenAccount>>,
+    gate_metadata: Option<&UncheckedAccount>,
+    instructions: Option<&UncheckedAccount>,
+) -> Result<()> {
+    match stream.gate {
+        StreamGate::None => Ok(()),
+        StreamGate::TokenBalance { mint, min_amount } => {
+            let account = gate_token_account.ok_or(CypherCastError::GateNotSatisfied)?;
+            require!(
+                account.owner == *viewer && account.mint == mint && account.amount >= min_amount,
+                CypherCastError::GateNotSatisfied
+            );
+            Ok(())
+        }
+        StreamGate::Collection { collection } => {
+            let account = gate_token_account.ok_or(CypherCastError::GateNotSatisfied)?;
+            let metadata = gate_metadata.ok_or(CypherCastError::GateNotSatisfied)?;
+            require!(
+                account.owner == *viewer && account.amount >= 1,
+                CypherCastError::GateNotSatisfied
+            );
+            require!(
+                *metadata.owner == metaplex::TOKEN_METADATA_PROGRAM_ID,
+                CypherCastError::GateNotSatisfied
+            );
+            let data = metadata.try_borrow_data()?;
+            let parsed =
+                metaplex::read_collection(&data).ok_or(CypherCastError::GateNotSatisfied)?;
+            require!(
+                parsed.mint == account.mint && parsed.verified_collection == Some(collection),
+                CypherCastError::GateNotSatisfied
+            );
+            Ok(())
+        }
+        StreamGate::Attestation { signer } => {
+            let instructions = instructions.ok_or(CypherCastError::InvalidAttestation)?;
+            let message = [stream.key().to_bytes(), viewer.to_bytes()].concat();
+            verify_ed25519_attestation(instructions, &signer, &message)
+        }
+    }
+}
+
+/// Require that the instruction before the current one is an ed25519 program verification
+/// of a single `signer` signature over exactly `message`, with all data inline.
+fn verify_ed25519_attestation(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    use anchor_lang::solana_program::{ed25519_program, sysvar::instructions as ix_sysvar};
+
+    let current = ix_sysvar::load_current_index_checked(instructions)?;
+    require!(current > 0, CypherCastError::InvalidAttestation);
+    let ix = ix_sysvar::load_instruction_at_checked((current - 1) as usize, instructions)?;
+    require!(
+        ix.program_id == ed25519_program::ID,
+        CypherCastError::InvalidAttestation
+    );
+
+    // Layout: [num_signatures, padding, 7 x u16 offsets, ...payload]
+    let data = &ix.data;
+    require!(
+        data.len() >= 16 && data[0] == 1,
+        CypherCastError::InvalidAttestation
+    );
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let pubkey_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    // Every referenced field must live in this instruction's own data
+    require!(
+        read_u16(4) == u16::MAX && read_u16(8) == u16::MAX && read_u16(14) == u16::MAX,
+        CypherCastError::InvalidAttestation
+    );
+
+    let pubkey = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(CypherCastError::InvalidAttestation)?;
+    let signed = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(CypherCastError::InvalidAttestation)?;
+    require!(
+        pubkey == signer.as_ref() && signed == message,
+        CypherCastError::InvalidAttestation
+    );
+    Ok(())
+}
+
+/// `pool * share / total`, floored. Returns 0 for an empty pool.
+fn pro_rata(pool: u64, share: impl Into<u128>, total: u128) -> Result<u64> {
+    if pool == 0 {
+        return Ok(0);
+    }
+    let amount = (pool as u128)
+        .checked_mul(share.into())
+        .ok_or(CypherCastError::Overflow)?
+        .checked_div(total)
+        .ok_or(CypherCastError::Overflow)?;
+    u64::try_from(amount).map_err(|_| CypherCastError::Overflow.into())
+}
+
+/// Count a resolved stream and its tip on the owner's profile, which the tip is paid to.
+fn record_resolution(stream: &Stream, profile: &mut CreatorProfile) -> Result<()> {
+    profile.streams_resolved = profile
+        .streams_resolved
+        .checked_add(1)
+        .ok_or(CypherCastError::Overflow)?;
+    profile.tips_earned = profile
+        .tips_earned
+        .checked_add(stream.tip_amount)
+        .ok_or(CypherCastError::Overflow)?;
+    Ok(())
+}
+
+/// Transfer tokens out of a stream vault, signed by the vault PDA.
+fn transfer_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    vault: &Account<'info, TokenVault>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    destination: &Account<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let signer_seeds: &[&[&[u8]]] = &[&[b"vault", vault.stream.as_ref(), &[vault.bump]]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        Transfer {
+            from: vault_token_account.to_account_info(),
+            to: destination.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::transfer(cpi_ctx, amount)
+}
+
+/// Transfer tokens out of a stream vault and record the release against the pool.
+fn release_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    vault: &mut Account<'info, TokenVault>,
+    vault_token_account: &Account<'info, TokenAccount>,
+    destination: &Account<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    transfer_from_vault(
+        token_program,
+        vault,
+        vault_token_account,
+        destination,
+        amount,
+    )?;
+
+    vault.total_released = vault
+        .total_released
+        .checked_add(amount)
+        .ok_or(CypherCastError::Overflow)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateStream<'info> {
+    /// Allocates the stream ID
+    #[account(
+        mut,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = Stream::SPACE,
+        seeds = [
+            b"stream",
+            creator.key().as_ref(),
+            creator_profile.next_stream_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCommunityVault<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// Minimal DAO authority for now (can be creator)
+    pub dao_authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [b"community_vault"],
+        bump,
+        payer = creator,
+        space = CommunityVault::SPACE
+    )]
+    pub community_vault: Account<'info, CommunityVault>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = token_mint,
+        associated_token::authority = community_vault
+    )]
+    pub community_vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTokenVault<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stream", stream.creator.as_ref(), stream.stream_id.to_le_bytes().as_ref()],
+        bump = stream.bump,
+    )]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        init,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump,
+        payer = creator,
+        space = TokenVault::SPACE,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = token_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JoinStream<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        init,
+        payer = viewer,
+        space = Participant::SPACE,
+        seeds = [b"participant", stream.key().as_ref(), viewer.key().as_ref()],
+        bump
+    )]
+    pub participant: Account<'info, Participant>,
+
+    // Entry fee accounts, required when `stream.entry_fee > 0`
+    #[account(
+        init,
+        payer = viewer,
+        space = AccessPass::SPACE,
+        seeds = [b"access_pass", stream.key().as_ref(), viewer.key().as_ref()],
+        bump
+    )]
+    pub access_pass: Option<Account<'info, AccessPass>>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Option<Account<'info, TokenVault>>,
+
+    #[account(
+        mut,
+        constraint = viewer_token_account.owner == viewer.key()
+    )]
+    pub viewer_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// Gate proof: token account for `TokenBalance` / `Collection` gates
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metaplex metadata of the gate NFT, parsed in `verify_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar, read to verify an `Attestation` gate
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub viewer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitPrediction<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        init,
+        payer = viewer,
+        space = Prediction::SPACE,
+        seeds = [b"prediction", stream.key().as_ref(), viewer.key().as_ref()],
+        bump
+    )]
+    pub prediction: Account<'info, Prediction>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = viewer_token_account.owner == viewer.key(),
+        constraint = viewer_token_account.mint == vault.mint
+    )]
+    pub viewer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Required when the stream charges an entry fee
+    #[account(
+        seeds = [b"access_pass", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = access_pass.bump
+    )]
+    pub access_pass: Option<Account<'info, AccessPass>>,
+
+    /// Required on invite-only streams and streams with per-viewer limits
+    #[account(
+        mut,
+        seeds = [b"participant", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Option<Account<'info, Participant>>,
+
+    /// Gate proof: token account for `TokenBalance` / `Collection` gates
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metaplex metadata of the gate NFT, parsed in `verify_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar, read to verify an `Attestation` gate
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub viewer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolvePrediction<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    /// Required when `authority` is a resolver rather than the owner
+    #[account(
+        seeds = [b"stream_roles", stream.key().as_ref()],
+        bump = stream_roles.bump
+    )]
+    pub stream_roles: Option<Account<'info, StreamRoles>>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == stream.owner,
+        constraint = creator_token_account.mint == vault.mint
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// The owner's profile, credited with the stream's stats
+    #[account(
+        mut,
+        seeds = [b"creator_profile", stream.owner.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateStream<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    /// Required once `initialize_stream_metadata` has run
+    #[account(
+        seeds = [b"stream_metadata", stream.key().as_ref()],
+        bump = stream_metadata.bump
+    )]
+    pub stream_metadata: Option<Account<'info, StreamMetadata>>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    /// The owner's profile, credited with the stream's stats
+    #[account(
+        mut,
+        seeds = [b"creator_profile", stream.owner.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ArbiterCancelStream<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub arbiter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        has_one = viewer,
+        constraint = prediction.stream == stream.key()
+    )]
+    pub prediction: Account<'info, Prediction>,
+
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = viewer_token_account.owner == viewer.key(),
+        constraint = viewer_token_account.mint == vault.mint
+    )]
+    pub viewer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub viewer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct EndStream<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    /// Required when `authority` is a moderator rather than the owner
+    #[account(
+        seeds = [b"stream_roles", stream.key().as_ref()],
+        bump = stream_roles.bump
+    )]
+    pub stream_roles: Option<Account<'info, StreamRoles>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReward<'info> {
+    /// Prediction account being claimed. Enforce that the prediction belongs to
+    /// the supplied stream and viewer via Anchor constraints.
+    #[account(
+        mut,
+        // ensure the prediction's viewer matches the signer
+        has_one = viewer,
+        // ensure the prediction's stream matches the provided stream account
+        constraint = prediction.stream == stream.key()
+    )]
+    pub prediction: Account<'info, Prediction>,
+
+    /// The stream that has been resolved. Must match `prediction.stream`.
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = viewer_token_account.owner == viewer.key(),
+        constraint = viewer_token_account.mint == vault.mint
+    )]
+    pub viewer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// The viewer (caller) claiming the reward. Must match `prediction.viewer`.
+    pub viewer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFixedOdds<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundHouseReserve<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == creator.key(),
+        constraint = creator_token_account.mint == vault.mint
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawHouseReserve<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == creator.key(),
+        constraint = creator_token_account.mint == vault.mint
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureLmsr<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteLmsr<'info> {
+    pub stream: Account<'info, Stream>,
+}
+
+#[derive(Accounts)]
+pub struct OpenLmsrPosition<'info> {
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        init,
+        payer = viewer,
+        space = LmsrPosition::SPACE,
+        seeds = [b"lmsr_position", stream.key().as_ref(), viewer.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, LmsrPosition>,
+
+    #[account(mut)]
+    pub viewer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TradeShares<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        has_one = viewer,
+        seeds = [b"lmsr_position", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, LmsrPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = viewer_token_account.owner == viewer.key(),
+        constraint = viewer_token_account.mint == vault.mint
+    )]
+    pub viewer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Required when the stream charges an entry fee
+    #[account(
+        seeds = [b"access_pass", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = access_pass.bump
+    )]
+    pub access_pass: Option<Account<'info, AccessPass>>,
+
+    /// Required on invite-only streams and streams with per-viewer limits
+    #[account(
+        mut,
+        seeds = [b"participant", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Option<Account<'info, Participant>>,
+
+    pub viewer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemShares<'info> {
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        has_one = viewer,
+        seeds = [b"lmsr_position", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = position.bump,
+    )]
+    pub position: Account<'info, LmsrPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = viewer_token_account.owner == viewer.key(),
+        constraint = viewer_token_account.mint == vault.mint
+    )]
+    pub viewer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub viewer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureOutcomeTokens<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(choice: u8)]
+pub struct CreateOutcomeMint<'info> {
+    pub stream: Account<'info, Stream>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(address = vault.mint)]
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"outcome_mint", stream.key().as_ref(), &[choice]],
+        bump,
+        mint::decimals = token_mint.decimals,
+        mint::authority = stream,
+    )]
+    pub outcome_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(choice: u8)]
+pub struct SubmitTokenizedPrediction<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        seeds = [b"outcome_mint", stream.key().as_ref(), &[choice]],
+        bump,
+    )]
+    pub outcome_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = outcome_mint,
+    )]
+    pub viewer_outcome_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = viewer_token_account.owner == viewer.key(),
+        constraint = viewer_token_account.mint == vault.mint
+    )]
+    pub viewer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Required when the stream charges an entry fee
+    #[account(
+        seeds = [b"access_pass", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = access_pass.bump
+    )]
+    pub access_pass: Option<Account<'info, AccessPass>>,
+
+    /// Required when the stream sets per-viewer limits; tracks cumulative stake
+    #[account(
+        mut,
+        seeds = [b"participant", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Option<Account<'info, Participant>>,
+
+    pub viewer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(choice: u8)]
+pub struct RedeemOutcomeTokens<'info> {
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        seeds = [b"outcome_mint", stream.key().as_ref(), &[choice]],
+        bump,
+    )]
+    pub outcome_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = outcome_mint,
+        token::authority = holder,
+    )]
+    pub holder_outcome_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = holder_token_account.owner == holder.key(),
+        constraint = holder_token_account.mint == vault.mint
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub holder: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(challenge_id: u64)]
+pub struct OpenChallenge<'info> {
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        init,
+        payer = challenger,
+        space = Challenge::SPACE,
+        seeds = [
+            b"challenge",
+            stream.key().as_ref(),
+            challenger.key().as_ref(),
+            challenge_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = challenger_token_account.owner == challenger.key(),
+        constraint = challenger_token_account.mint == vault.mint
+    )]
+    pub challenger_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Required when the stream charges an entry fee
+    #[account(
+        seeds = [b"access_pass", stream.key().as_ref(), challenger.key().as_ref()],
+        bump = access_pass.bump
+    )]
+    pub access_pass: Option<Account<'info, AccessPass>>,
+
+    /// Required on invite-only streams and streams with per-viewer limits
+    #[account(
+        mut,
+        seeds = [b"participant", stream.key().as_ref(), challenger.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Option<Account<'info, Participant>>,
+
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptChallenge<'info> {
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        constraint = challenge.stream == stream.key()
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = acceptor_token_account.owner == acceptor.key(),
+        constraint = acceptor_token_account.mint == vault.mint
+    )]
+    pub acceptor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Required when the stream charges an entry fee
+    #[account(
+        seeds = [b"access_pass", stream.key().as_ref(), acceptor.key().as_ref()],
+        bump = access_pass.bump
+    )]
+    pub access_pass: Option<Account<'info, AccessPass>>,
+
+    /// Required on invite-only streams and streams with per-viewer limits
+    #[account(
+        mut,
+        seeds = [b"participant", stream.key().as_ref(), acceptor.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Option<Account<'info, Participant>>,
+
+    pub acceptor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelChallenge<'info> {
+    #[account(
+        mut,
+        has_one = challenger,
+        close = challenger
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        seeds = [b"vault", challenge.stream.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = challenger_token_account.owner == challenger.key(),
+        constraint = challenger_token_account.mint == vault.mint
+    )]
+    pub challenger_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Releases the escrow from the challenger's per-viewer stake when passed
+    #[account(
+        mut,
+        seeds = [b"participant", challenge.stream.as_ref(), challenger.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Option<Account<'info, Participant>>,
+
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SettleChallenge<'info> {
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        constraint = challenge.stream == stream.key(),
+        close = challenger
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = challenger_token_account.owner == challenge.challenger,
+        constraint = challenger_token_account.mint == vault.mint
+    )]
+    pub challenger_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = acceptor_token_account.owner == challenge.acceptor,
+        constraint = acceptor_token_account.mint == vault.mint
+    )]
+    pub acceptor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: rent recipient for the closed challenge, must be the challenger
+    #[account(mut, address = challenge.challenger)]
+    pub challenger: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureScalar<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TallyPrediction<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        constraint = prediction.stream == stream.key()
+    )]
+    pub prediction: Account<'info, Prediction>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRanked<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeParlayPool<'info> {
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = ParlayPool::SPACE,
+        seeds = [b"parlay_pool", stream.key().as_ref()],
+        bump
+    )]
+    pub parlay_pool: Account<'info, ParlayPool>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(parlay_id: u64)]
+pub struct PlaceParlay<'info> {
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        seeds = [b"parlay_pool", stream.key().as_ref()],
+        bump = parlay_pool.bump,
+    )]
+    pub parlay_pool: Account<'info, ParlayPool>,
+
+    #[account(
+        init,
+        payer = bettor,
+        space = Parlay::SPACE,
+        seeds = [
+            b"parlay",
+            parlay_pool.key().as_ref(),
+            bettor.key().as_ref(),
+            parlay_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub parlay: Account<'info, Parlay>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key(),
+        constraint = bettor_token_account.mint == vault.mint
+    )]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleParlay<'info> {
+    #[account(mut)]
+    pub parlay_pool: Account<'info, ParlayPool>,
+
+    #[account(
+        mut,
+        constraint = parlay.pool == parlay_pool.key()
+    )]
+    pub parlay: Account<'info, Parlay>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimParlay<'info> {
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        seeds = [b"parlay_pool", stream.key().as_ref()],
+        bump = parlay_pool.bump,
+    )]
+    pub parlay_pool: Account<'info, ParlayPool>,
+
+    #[account(
+        mut,
+        has_one = bettor,
+        constraint = parlay.pool == parlay_pool.key()
+    )]
+    pub parlay: Account<'info, Parlay>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == bettor.key(),
+        constraint = bettor_token_account.mint == vault.mint
+    )]
+    pub bettor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub bettor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(choice: u8)]
+pub struct SeedLiquidity<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        init,
+        payer = seeder,
+        space = LiquiditySeed::SPACE,
+        seeds = [
+            b"liquidity_seed",
+            stream.key().as_ref(),
+            seeder.key().as_ref(),
+            &[choice]
+        ],
+        bump
+    )]
+    pub liquidity_seed: Account<'info, LiquiditySeed>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = seeder_token_account.owner == seeder.key(),
+        constraint = seeder_token_account.mint == vault.mint
+    )]
+    pub seeder_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// Required on invite-only streams
+    #[account(
+        seeds = [b"participant", stream.key().as_ref(), seeder.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Option<Account<'info, Participant>>,
+
+    #[account(mut)]
+    pub seeder: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimSeed<'info> {
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        has_one = seeder,
+        constraint = liquidity_seed.stream == stream.key()
+    )]
+    pub liquidity_seed: Account<'info, LiquiditySeed>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = seeder_token_account.owner == seeder.key(),
+        constraint = seeder_token_account.mint == vault.mint
+    )]
+    pub seeder_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub seeder: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SponsorStream<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        init,
+        payer = sponsor,
+        space = Sponsorship::SPACE,
+        seeds = [
+            b"sponsorship",
+            stream.key().as_ref(),
+            stream.sponsorship_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub sponsorship: Account<'info, Sponsorship>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = sponsor_token_account.owner == sponsor.key(),
+        constraint = sponsor_token_account.mint == vault.mint
+    )]
+    pub sponsor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimSponsorship<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        has_one = sponsor,
+        constraint = sponsorship.stream == stream.key()
+    )]
+    pub sponsorship: Account<'info, Sponsorship>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = sponsor_token_account.owner == sponsor.key(),
+        constraint = sponsor_token_account.mint == vault.mint
+    )]
+    pub sponsor_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub sponsor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFreeToPlay<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeLock<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+}
+
+/// Creator-only update of a Draft stream's settings.
+#[derive(Accounts)]
+pub struct ConfigureStream<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitFreePrediction<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        seeds = [b"participant", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Account<'info, Participant>,
+
+    #[account(
+        init,
+        payer = viewer,
+        space = Prediction::SPACE,
+        seeds = [b"prediction", stream.key().as_ref(), viewer.key().as_ref()],
+        bump
+    )]
+    pub prediction: Account<'info, Prediction>,
+
+    /// Gate proof: token account for `TokenBalance` / `Collection` gates
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metaplex metadata of the gate NFT, parsed in `verify_gate`
+    pub gate_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar, read to verify an `Attestation` gate
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub viewer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureEntryFee<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeEntryFees<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == stream.owner,
+        constraint = creator_token_account.mint == vault.mint
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"community_vault"],
+        bump = community_vault.bump,
+        constraint = community_vault.mint == vault.mint
+    )]
+    pub community_vault: Account<'info, CommunityVault>,
+
+    #[account(
+        mut,
+        constraint = community_vault_token_account.key() == community_vault.token_account
+    )]
+    pub community_vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RefundAccessPass<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        seeds = [b"access_pass", stream.key().as_ref(), access_pass.viewer.as_ref()],
+        bump = access_pass.bump
+    )]
+    pub access_pass: Account<'info, AccessPass>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = viewer_token_account.owner == access_pass.viewer,
+        constraint = viewer_token_account.mint == vault.mint
+    )]
+    pub viewer_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStreamRoles<'info> {
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = StreamRoles::SPACE,
+        seeds = [b"stream_roles", stream.key().as_ref()],
+        bump
+    )]
+    pub stream_roles: Account<'info, StreamRoles>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageStreamRoles<'info> {
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [b"stream_roles", stream.key().as_ref()],
+        bump = stream_roles.bump
+    )]
+    pub stream_roles: Account<'info, StreamRoles>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [b"stream_roles", stream.key().as_ref()],
+        bump = stream_roles.bump
+    )]
+    pub stream_roles: Account<'info, StreamRoles>,
+
+    /// The new owner must have a profile to credit the stream's stats to
+    #[account(
+        seeds = [b"creator_profile", new_owner.key().as_ref()],
+        bump = new_owner_profile.bump
+    )]
+    pub new_owner_profile: Account<'info, CreatorProfile>,
+
+    pub new_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ModerateStream<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    /// Required when `authority` is a moderator rather than the creator
+    #[account(
+        seeds = [b"stream_roles", stream.key().as_ref()],
+        bump = stream_roles.bump
+    )]
+    pub stream_roles: Option<Account<'info, StreamRoles>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RescheduleStream<'info> {
+    #[account(mut)]
+    pub stream: Account<'info, Stream>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawPrediction<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        close = viewer,
+        has_one = viewer,
+        constraint = prediction.stream == stream.key()
+    )]
+    pub prediction: Account<'info, Prediction>,
+
+    /// Required on invite-only streams and streams with per-viewer limits
+    #[account(
+        mut,
+        seeds = [b"participant", stream.key().as_ref(), viewer.key().as_ref()],
+        bump = participant.bump
+    )]
+    pub participant: Option<Account<'info, Participant>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = viewer_token_account.owner == viewer.key(),
+        constraint = viewer_token_account.mint == vault.mint
+    )]
+    pub viewer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub viewer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct PostBond<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == creator.key(),
+        constraint = creator_token_account.mint == vault.mint
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimBond<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = poster_token_account.owner == stream.bond_poster,
+        constraint = poster_token_account.mint == vault.mint
+    )]
+    pub poster_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeSlashedBond<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        seeds = [b"vault", stream.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, TokenVault>,
+
+    #[account(
+        mut,
+        constraint = vault_token_account.key() == vault.token_account,
+        constraint = vault_token_account.mint == vault.mint
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"community_vault"],
+        bump = community_vault.bump,
+        constraint = community_vault.mint == vault.mint
+    )]
+    pub community_vault: Account<'info, CommunityVault>,
+
+    #[account(
+        mut,
+        constraint = community_vault_token_account.key() == community_vault.token_account
+    )]
+    pub community_vault_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCreatorProfile<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = CreatorProfile::SPACE,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCreatorProfile<'info> {
+    #[account(
+        mut,
+        has_one = creator,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGlobalConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = GlobalConfig::SPACE,
+        seeds = [b"global_config"],
+        bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Cyphercast>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ CypherCastError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageGlobalConfig<'info> {
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(handle: String)]
+pub struct ClaimHandle<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = HandleRecord::SPACE,
+        seeds = [b"handle", handle.as_bytes()],
+        bump
+    )]
+    pub handle_record: Account<'info, HandleRecord>,
+
+    #[account(
+        mut,
+        has_one = creator,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(seeds = [b"global_config"], bump = global_config.bump)]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseHandle<'info> {
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"handle", handle_record.handle.as_bytes()],
+        bump = handle_record.bump,
+        constraint = handle_record.owner == creator.key() @ CypherCastError::Unauthorized
+    )]
+    pub handle_record: Account<'info, HandleRecord>,
+
+    #[account(
+        mut,
+        has_one = creator,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferHandle<'info> {
+    #[account(
+        mut,
+        seeds = [b"handle", handle_record.handle.as_bytes()],
+        bump = handle_record.bump,
+        constraint = handle_record.owner == creator.key() @ CypherCastError::Unauthorized
+    )]
+    pub handle_record: Account<'info, HandleRecord>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptHandle<'info> {
+    #[account(
+        mut,
+        seeds = [b"handle", handle_record.handle.as_bytes()],
+        bump = handle_record.bump
+    )]
+    pub handle_record: Account<'info, HandleRecord>,
+
+    #[account(
+        mut,
+        constraint = previous_profile.key() == handle_record.profile
+    )]
+    pub previous_profile: Account<'info, CreatorProfile>,
+
+    /// Profile of the accepting creator
+    #[account(
+        mut,
+        has_one = creator,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(description: String, category: StreamCategory, tags: Vec<String>, media_uri: String)]
+pub struct InitializeStreamMetadata<'info> {
+    #[account(mut)]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = StreamMetadata::space(&description, &tags, &media_uri),
+        seeds = [b"stream_metadata", stream.key().as_ref()],
+        bump
+    )]
+    pub stream_metadata: Account<'info, StreamMetadata>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(description: String, category: StreamCategory, tags: Vec<String>, media_uri: String)]
+pub struct UpdateStreamMetadata<'info> {
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        seeds = [b"stream_metadata", stream.key().as_ref()],
+        bump = stream_metadata.bump,
+        realloc = StreamMetadata::space(&description, &tags, &media_uri),
+        realloc::payer = creator,
+        realloc::zero = false
+    )]
+    pub stream_metadata: Account<'info, StreamMetadata>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct Stream {
+    pub creator: Pubkey,
+    pub stream_id: u64,
+    pub title: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    // Phase 2.5 config fields
+    pub lock_offset_secs: i64,
+    pub grace_period_secs: i64,
+    pub tip_bps: u16,  // 0..=10_000
+    pub precision: u8, // <= 9
+    pub config_hash: [u8; 32],
+    // Aggregates
+    pub total_stake: u64,
+    pub total_by_choice: [u64; 11],
+    // Legacy flags (kept for compatibility while introducing state machine)
+    pub is_active: bool,
+    pub is_resolved: bool,
+    pub winning_choice: u8,
+    // Phase 2.5 runtime fields
+    pub tip_amount: u64,
+    pub resolved_at: i64,
+    pub canceled_at: i64,
+    // Payout mode
+    pub market_mode: MarketMode,
+    pub odds_bps: [u32; 11], // fixed-odds multiplier per choice (10_000 = 1x)
+    pub house_reserve: u64,  // creator-funded liability reserve
+    pub liability_by_choice: [u64; 11], // worst-case house payout if the choice wins
+    pub house_settled: bool, // reserve leftover returned to creator
+    pub lmsr_b: u64,         // LMSR liquidity parameter
+    pub lmsr_outcomes: u8,   // number of tradeable LMSR choices
+    pub shares_by_choice: [u64; 11], // outstanding LMSR shares per choice
+    pub refund_liability: u64, // sum of LMSR position cost bases (refunds on cancel)
+    // Scalar markets (values are fixed point with `precision` decimals)
+    pub scalar_min: i64,
+    pub scalar_max: i64,
+    pub scalar_scoring: ScalarScoring,
+    pub scalar_result: i64,
+    // Weighted settlement (scalar): predictions are tallied one by one after resolution
+    pub prediction_count: u64,
+    pub tallied_count: u64,
+    pub total_weight: u128,
+    // Multi-winner resolution
+    pub winner_weights: [u16; 11], // payout weight per winning choice (0 = lost)
+    pub is_void: bool,             // resolved as void: full-refund mode
+    // Ranked markets
+    pub rank_depth: u8,
+    pub rank_points: RankPoints,
+    pub final_ranking: [u8; MAX_RANK_DEPTH],
+    // Liquidity seeded during Draft (already included in `total_by_choice`)
+    pub seeded_by_choice: [u64; 11],
+    // Sponsor bonus paid to winners on top of the pool (outside `total_deposited`)
+    pub bonus_pool: u64,
+    pub sponsorship_count: u64,
+    pub free_play_split: FreePlaySplit,
+    pub gate: StreamGate,
+    // Entry fee charged by `join_stream` (0 = free) and its escrow bookkeeping
+    pub entry_fee: u64,
+    pub entry_fee_split: EntryFeeSplit,
+    pub entry_fees_collected: u64,
+    pub entry_fee_prize: u64,
+    pub entry_fees_distributed: bool,
+    // Merkle root of invited viewers (all zeroes = open stream)
+    pub allowlist_root: [u8; 32],
+    // Anti-whale limits (0 = disabled)
+    pub min_stake: u64,
+    pub max_stake_per_viewer: u64,
+    pub max_share_bps_of_choice: u16,
+    // Pool depth below which the share limit is not applied
+    pub share_limit_min_pool: u64,
+    // Participation threshold checked by `finalize_lock` (0 / false = disabled)
+    pub min_participants: u64,
+    pub min_total_stake: u64,
+    pub require_two_sided: bool,
+    pub lock_finalized: bool,
+    // Manual lock time stamped by `lock_stream` (0 = scheduled lock only)
+    pub locked_at: i64,
+    pub is_paused: bool,
+    // Reschedule policy and the lock extension granted so far (on top of `lock_offset_secs`)
+    pub max_lock_extension_secs: i64,
+    pub withdrawal_window_secs: i64,
+    pub lock_extension_secs: i64,
+    pub rescheduled_at: i64,
+    pub withdrawal_deadline: i64,
+    // Time after the lock before the outcome may be resolved
+    pub min_observation_secs: i64,
+    // Creator bond, forfeited on cancellation after lock (0 = no bond)
+    pub bond_amount: u64,
+    pub bond_slash_target: BondSlashTarget,
+    // May cancel after lock without slashing the bond (default key = none)
+    pub arbiter: Pubkey,
+    pub bond_posted: bool,
+    pub bond_slashed: bool,
+    pub bond_released: bool,
+    pub bond_poster: Pubkey,   // wallet that posted the bond and gets it back
+    pub bond_distributed: u64, // slashed bond paid out as refund shares
+    pub refunded_stake: u64,   // prediction stake refunded while the bond was slashed
+    // Administrative authority; starts as the creator, changed by `accept_ownership`
+    pub owner: Pubkey,
+    // A `StreamMetadata` account exists and must be hashed at activation
+    pub has_metadata: bool,
+    pub bump: u8,
+}
+
+impl Stream {
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // creator
+        8 + // stream_id
+        4 + 200 + // title (max 200 chars)
+        8 + // start_time
+        8 + // end_time
+        8 + // lock_offset_secs
+        8 + // grace_period_secs
+        2 + // tip_bps
+        1 + // precision
+        32 + // config_hash
+        8 + // total_stake
+        (8 * 11) + // total_by_choice array (11 choices: 0..=10)
+        1 + // is_active
+        1 + // is_resolved
+        1 + // winning_choice (u8)
+        8 + // tip_amount
+        8 + // resolved_at
+        8 + // canceled_at
+        1 + // market_mode
+        (4 * 11) + // odds_bps
+        8 + // house_reserve
+        (8 * 11) + // liability_by_choice
+        1 + // house_settled
+        8 + // lmsr_b
+        1 + // lmsr_outcomes
+        (8 * 11) + // shares_by_choice
+        8 + // refund_liability
+        8 + // scalar_min
+        8 + // scalar_max
+        ScalarScoring::SPACE + // scalar_scoring
+        8 + // scalar_result
+        8 + // prediction_count
+        8 + // tallied_count
+        16 + // total_weight
+        (2 * 11) + // winner_weights
+        1 + // is_void
+        1 + // rank_depth
+        RankPoints::SPACE + // rank_points
+        MAX_RANK_DEPTH + // final_ranking
+        (8 * 11) + // seeded_by_choice
+        8 + // bonus_pool
+        8 + // sponsorship_count
+        1 + // free_play_split
+        StreamGate::SPACE + // gate
+        8 + // entry_fee
+        EntryFeeSplit::SPACE + // entry_fee_split
+        8 + // entry_fees_collected
+        8 + // entry_fee_prize
+        1 + // entry_fees_distributed
+        32 + // allowlist_root
+        8 + // min_stake
+        8 + // max_stake_per_viewer
+        2 + // max_share_bps_of_choice
+        8 + // share_limit_min_pool
+        8 + // min_participants
+        8 + // min_total_stake
+        1 + // require_two_sided
+        1 + // lock_finalized
+        8 + // locked_at
+        1 + // is_paused
+        8 + // max_lock_extension_secs
+        8 + // withdrawal_window_secs
+        8 + // lock_extension_secs
+        8 + // rescheduled_at
+        8 + // withdrawal_deadline
+        8 + // min_observation_secs
+        8 + // bond_amount
+        1 + // bond_slash_target
+        32 + // arbiter
+        1 + // bond_posted
+        1 + // bond_slashed
+        1 + // bond_released
+        32 + // bond_poster
+        8 + // bond_distributed
+        8 + // refunded_stake
+        32 + // owner
+        1 + // has_metadata
+        1; // bump
+
+    /// A stream is in Draft until activation freezes its config hash.
+    pub fn is_draft(&self) -> bool {
+        self.config_hash == [0u8; 32]
+    }
+
+    /// Fixed-odds payout for a stake on `choice`, floored to whole token units.
+    pub fn fixed_odds_payout(&self, choice: u8, stake_amount: u64) -> Result<u64> {
+        let payout = (stake_amount as u128)
+            .checked_mul(self.odds_bps[choice as usize] as u128)
+            .ok_or(CypherCastError::Overflow)?
+            / 10_000u128;
+        u64::try_from(payout).map_err(|_| CypherCastError::Overflow.into())
+    }
+
+    /// Modes where the creator underwrites payouts from `house_reserve`.
+    pub fn is_house_backed(&self) -> bool {
+        matches!(self.market_mode, MarketMode::FixedOdds | MarketMode::Lmsr)
+    }
+
+    /// LMSR cost (buy) or proceeds (sell) for `shares` of `choice` at current quantities.
+    pub fn lmsr_quote(&self, choice: u8, shares: u64, buy: bool) -> Result<u64> {
+        let quantities = &self.shares_by_choice[..self.lmsr_outcomes as usize];
+        let quote = if buy {
+            lmsr::buy_cost(quantities, self.lmsr_b, choice as usize, shares)
+        } else {
+            lmsr::sell_proceeds(quantities, self.lmsr_b, choice as usize, shares)
+        };
+        quote.ok_or(CypherCastError::Overflow.into())
+    }
+
+    /// Effective lock time: the scheduled `start_time + lock_offset_secs` plus any granted
+    /// extension, or the manual `locked_at` if the stream was locked earlier.
+    pub fn lock_time(&self) -> Result<i64> {
+        let scheduled = self
+            .start_time
+            .checked_add(self.lock_offset_secs)
+            .and_then(|t| t.checked_add(self.lock_extension_secs))
+            .ok_or(CypherCastError::Overflow)?;
+        Ok(if self.locked_at != 0 {
+            scheduled.min(self.locked_at)
+        } else {
+            scheduled
+        })
+    }
+
+    /// Reject new positions while predictions are paused or the stream is locked.
+    pub fn require_open_for_predictions(&self, now: i64) -> Result<()> {
+        require!(!self.is_paused, CypherCastError::PredictionsPaused);
+        require!(now < self.lock_time()?, CypherCastError::StreamLocked);
+        Ok(())
+    }
+
+    /// Earliest time the outcome may be resolved: the lock plus the observation window.
+    pub fn resolvable_at(&self) -> Result<i64> {
+        self.lock_time()?
+            .checked_add(self.min_observation_secs)
+            .ok_or(CypherCastError::Overflow.into())
+    }
+
+    /// Gate shared by the resolve instructions. Requires the lock and the observation
+    /// window to have passed, then ends the stream if `end_stream` was never called.
+    pub fn end_for_resolution(&mut self, now: i64) -> Result<()> {
+        require!(now >= self.lock_time()?, CypherCastError::StreamNotLocked);
+        require!(
+            now >= self.resolvable_at()?,
+            CypherCastError::ObservationWindowOpen
+        );
+        if self.is_active {
+            self.is_active = false;
+            self.end_time = now;
+        }
+        Ok(())
+    }
+
+    /// Whether `finalize_lock` must pass before the stream can be resolved.
+    pub fn has_participation_threshold(&self) -> bool {
+        self.min_participants > 0 || self.min_total_stake > 0 || self.require_two_sided
+    }
+
+    /// Number of choices backed by viewers (shares for LMSR markets), ignoring seeds.
+    pub fn backed_choice_count(&self) -> usize {
+        if self.market_mode == MarketMode::Lmsr {
+            return self.shares_by_choice.iter().filter(|&&s| s > 0).count();
+        }
+        self.total_by_choice
+            .iter()
+            .zip(self.seeded_by_choice.iter())
+            .filter(|(&total, &seeded)| total > seeded)
+            .count()
+    }
+
+    /// Whether stakes must be recorded on the viewer's `Participant` (allowlist or
+    /// per-viewer limits).
+    pub fn tracks_participants(&self) -> bool {
+        self.allowlist_root != [0u8; 32]
+            || self.max_stake_per_viewer > 0
+            || self.max_share_bps_of_choice > 0
+    }
+
+    /// Whether every pool a share limit applies to can be given depth before its first
+    /// stake: Parimutuel choices through `seed_liquidity`, LMSR through its parameter `b`.
+    pub fn share_pools_seedable(&self) -> bool {
+        matches!(self.market_mode, MarketMode::Parimutuel | MarketMode::Lmsr)
+    }
+
+    /// Check the anti-whale limits against each other and the market mode.
+    pub fn validate_stake_limits(&self) -> Result<()> {
+        validation::validate_stake_limits(
+            self.min_stake,
+            self.max_stake_per_viewer,
+            self.max_share_bps_of_choice,
+            self.share_limit_min_pool,
+            self.share_pools_seedable(),
+        )?;
+        Ok(())
+    }
+
+    /// Pool stake placed by viewers, excluding seeded liquidity.
+    pub fn viewer_stake(&self) -> u64 {
+        let seeded: u64 = self.seeded_by_choice.iter().sum();
+        self.total_stake.saturating_sub(seeded)
+    }
+
+    /// Stake refunded through `claim_refund`, which shares a slashed bond. LMSR and
+    /// outcome-token positions refund through their own instructions and take no share.
+    pub fn bond_refundable_stake(&self) -> u64 {
+        match self.market_mode {
+            MarketMode::Lmsr | MarketMode::OutcomeTokens => 0,
+            _ => self.viewer_stake(),
+        }
+    }
+
+    /// Refunds are open when the stream was canceled or resolved as void.
+    pub fn is_refund_mode(&self) -> bool {
+        self.canceled_at != 0 || self.is_void
+    }
+
+    /// Pool shared by winners: vault deposits minus the streamer tip, plus sponsor bonus.
+    pub fn distributable(&self, total_deposited: u64) -> Result<u64> {
+        total_deposited
+            .checked_sub(self.tip_amount)
+            .and_then(|pool| pool.checked_add(self.bonus_pool))
+            .ok_or(CypherCastError::Overflow.into())
+    }
+
+    /// Parimutuel payout for `amount` staked on a winning `choice`.
+    /// The pool is first sliced by winner weight (ignoring winners nobody backed), then the
+    /// choice's slice is split pro rata to stake.
+    pub fn choice_payout(&self, distributable: u64, choice: u8, amount: u64) -> Result<u64> {
+        let weight = self.winner_weights[choice as usize] as u128;
+        let total_weight: u128 = self
+            .winner_weights
+            .iter()
+            .zip(self.total_by_choice.iter())
+            .filter(|(_, &staked)| staked > 0)
+            .map(|(&w, _)| w as u128)
+            .sum();
+        let choice_total = self.total_by_choice[choice as usize];
+        require!(
+            total_weight > 0 && choice_total > 0,
+            CypherCastError::NoWinner
+        );
+
+        let slice = pro_rata(distributable, weight, total_weight)?;
+        pro_rata(slice, amount, choice_total as u128)
+    }
 
-    #[account(
-        mut,
-        constraint = viewer_token_account.owner == viewer.key(),
-        constraint = viewer_token_account.mint == vault.mint
-    )]
-    pub viewer_token_account: Account<'info, TokenAccount>,
+    /// Whether a resolved stream has nobody to pay: no winning choice holds any stake, or
+    /// (scalar and ranked, once every prediction is tallied) no prediction earned weight.
+    pub fn has_no_winners(&self) -> bool {
+        if self.resolves_to_choice() {
+            !self
+                .winner_weights
+                .iter()
+                .zip(self.total_by_choice.iter())
+                .any(|(&weight, &staked)| weight > 0 && staked > 0)
+        } else {
+            self.tallied_count == self.prediction_count && self.total_weight == 0
+        }
+    }
 
-    #[account(
-        mut,
-        constraint = vault_token_account.key() == vault.token_account,
-        constraint = vault_token_account.mint == vault.mint
-    )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    /// Whether resolution picks a `winning_choice` (as opposed to a value or ranking).
+    pub fn resolves_to_choice(&self) -> bool {
+        !matches!(self.market_mode, MarketMode::Scalar | MarketMode::Ranked)
+    }
 
-    #[account(mut)]
-    pub viewer: Signer<'info>,
+    /// Validate an ordering of exactly `rank_depth` distinct choices.
+    pub fn parse_ranking(&self, ranking: &[u8]) -> Result<[u8; MAX_RANK_DEPTH]> {
+        require!(
+            ranking.len() == self.rank_depth as usize,
+            CypherCastError::InvalidRanking
+        );
+        let mut parsed = [0u8; MAX_RANK_DEPTH];
+        for (i, &choice) in ranking.iter().enumerate() {
+            require!(choice <= MAX_CHOICES, CypherCastError::InvalidChoice);
+            require!(
+                !ranking[..i].contains(&choice),
+                CypherCastError::InvalidRanking
+            );
+            parsed[i] = choice;
+        }
+        Ok(parsed)
+    }
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    /// Settlement weight of a prediction once the stream is resolved.
+    /// Ranked: `stake * points` against the final ranking.
+    /// Scalar: `InverseDistance` scales the stake by `1 / (1 + |error|)` with the error in
+    /// whole units (`10^precision`); `WithinBand` counts the full stake when the error is
+    /// at most `band` and nothing otherwise.
+    pub fn prediction_weight(&self, prediction: &Prediction) -> Result<u128> {
+        match self.market_mode {
+            MarketMode::Scalar => {
+                let distance = prediction.scalar_value.abs_diff(self.scalar_result) as u128;
+                let stake = prediction.stake_amount as u128;
+                let weight = match self.scalar_scoring {
+                    ScalarScoring::InverseDistance => {
+                        let unit = 10u128.pow(self.precision as u32);
+                        stake
+                            .checked_mul(WEIGHT_SCALE)
+                            .and_then(|v| v.checked_mul(unit))
+                            .ok_or(CypherCastError::Overflow)?
+                            / (unit + distance)
+                    }
+                    ScalarScoring::WithinBand { band } => {
+                        if distance <= band as u128 {
+                            stake * WEIGHT_SCALE
+                        } else {
+                            0
+                        }
+                    }
+                };
+                Ok(weight)
+            }
+            // Ranked: stake-weighted points, so payouts scale with each viewer's points share
+            MarketMode::Ranked => {
+                let depth = self.rank_depth as usize;
+                let actual = &self.final_ranking[..depth];
+                let points: u128 = prediction.ranking[..depth]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, choice)| {
+                        if actual[i] == *choice {
+                            self.rank_points.exact as u128
+                        } else if actual.contains(choice) {
+                            self.rank_points.in_top as u128
+                        } else {
+                            0
+                        }
+                    })
+                    .sum();
+                Ok(prediction.stake_amount as u128 * points)
+            }
+            _ => err!(CypherCastError::UnsupportedMarketMode),
+        }
+    }
+
+    /// Shared guards for LMSR buys and sells.
+    fn require_lmsr_trading(&self, choice: u8, shares: u64) -> Result<()> {
+        require!(
+            self.market_mode == MarketMode::Lmsr,
+            CypherCastError::UnsupportedMarketMode
+        );
+        require!(self.is_active, CypherCastError::StreamNotActive);
+        require!(self.canceled_at == 0, CypherCastError::Canceled);
+        require!(choice < self.lmsr_outcomes, CypherCastError::InvalidChoice);
+        require!(shares > 0, CypherCastError::InvalidStakeAmount);
+        let now = Clock::get()?.unix_timestamp;
+        self.require_open_for_predictions(now)?;
+        Ok(())
+    }
+
+    /// Tokens backing LMSR refunds: the house reserve plus net trader deposits.
+    pub fn lmsr_refund_funds(&self) -> Result<u64> {
+        self.house_reserve
+            .checked_add(self.total_stake)
+            .ok_or(CypherCastError::Overflow.into())
+    }
+
+    /// Refund for an LMSR position on a canceled market. Cost bases are paid in full when
+    /// the funds cover them, otherwise pro rata, so cycled trades between colluding
+    /// wallets can never drain more than the market holds.
+    pub fn lmsr_refund(&self, cost_basis: u64) -> Result<u64> {
+        if self.refund_liability == 0 {
+            return Ok(0);
+        }
+        let funds = self.lmsr_refund_funds()?;
+        pro_rata(
+            funds.min(self.refund_liability),
+            cost_basis,
+            self.refund_liability as u128,
+        )
+    }
+
+    /// Keep `refund_liability` equal to the sum of all LMSR position cost bases.
+    fn update_refund_liability(&mut self, old_basis: u64, new_basis: u64) -> Result<()> {
+        self.refund_liability = self
+            .refund_liability
+            .checked_sub(old_basis)
+            .and_then(|v| v.checked_add(new_basis))
+            .ok_or(CypherCastError::Overflow)?;
+        Ok(())
+    }
 }
 
-#[derive(Accounts)]
-pub struct ResolvePrediction<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+/// How winners of a stream are paid.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarketMode {
+    /// Winners split the pool pro rata to their stake (default).
+    Parimutuel,
+    /// Winners receive `stake * odds`, underwritten by the creator's house reserve.
+    FixedOdds,
+    /// Viewers trade shares priced by an LMSR market maker; a winning share redeems for one unit.
+    Lmsr,
+    /// Parimutuel pool whose positions are bearer SPL outcome tokens minted per choice.
+    OutcomeTokens,
+    /// Viewers predict a number; the pool is split by closeness to the resolved value.
+    Scalar,
+    /// Viewers predict the order of the top choices; the pool is split by points.
+    Ranked,
+    /// Predictions carry no stake; winners split the sponsor-funded bonus pool.
+    FreeToPlay,
+}
 
-    pub creator: Signer<'info>,
+/// How free-to-play winners split the prize.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FreePlaySplit {
+    /// Every winning entry receives the same share.
+    Equal,
+    /// Entries weigh the seconds left before lock when they were submitted.
+    EntryWeight,
+}
 
-    #[account(
-        mut,
-        seeds = [b"vault", stream.key().as_ref()],
-        bump = vault.bump,
-    )]
-    pub vault: Account<'info, TokenVault>,
+/// Delegated stream roles held in `StreamRoles`. The owner holds every role implicitly.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StreamRole {
+    /// Locks, pauses, resumes and ends the stream.
+    Moderator,
+    /// Resolves the stream.
+    Resolver,
+}
 
-    #[account(
-        mut,
-        constraint = creator_token_account.owner == creator.key(),
-        constraint = creator_token_account.mint == vault.mint
-    )]
-    pub creator_token_account: Account<'info, TokenAccount>,
+/// Dashboard category of a stream.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StreamCategory {
+    Other,
+    Gaming,
+    Esports,
+    Sports,
+    Music,
+    Talk,
+    Creative,
+}
 
-    #[account(
-        mut,
-        constraint = vault_token_account.key() == vault.token_account,
-        constraint = vault_token_account.mint == vault.mint
-    )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+/// Where a creator bond goes when it is slashed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BondSlashTarget {
+    /// Shared pro rata to stake through `claim_refund`.
+    Predictors,
+    /// Sent to the `CommunityVault` by `distribute_slashed_bond`.
+    CommunityVault,
+}
 
-    pub token_program: Program<'info, Token>,
+/// Anti-sybil requirement a viewer must meet to enter a stream.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StreamGate {
+    /// Open to every participant.
+    None,
+    /// Viewer must hold at least `min_amount` of `mint`.
+    TokenBalance { mint: Pubkey, min_amount: u64 },
+    /// Viewer must present an ed25519 signature by `signer` over `stream || viewer`.
+    Attestation { signer: Pubkey },
+    /// Viewer must hold an NFT from the verified Metaplex `collection`.
+    Collection { collection: Pubkey },
 }
 
-#[derive(Accounts)]
-pub struct ActivateStream<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+impl StreamGate {
+    pub const SPACE: usize = 1 + 32 + 8; // variant tag + largest payload
 
-    pub creator: Signer<'info>,
+    pub fn validate(&self) -> Result<()> {
+        if let StreamGate::TokenBalance { min_amount, .. } = self {
+            require!(*min_amount > 0, CypherCastError::InvalidConfig);
+        }
+        Ok(())
+    }
 }
 
-#[derive(Accounts)]
-pub struct CancelStream<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+/// Proof that a viewer is on a stream's allowlist.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct AllowlistProof {
+    /// Cumulative stake cap committed in the viewer's leaf (0 = no cap).
+    pub stake_cap: u64,
+    /// Sibling hashes from the leaf up to the root.
+    pub proof: Vec<[u8; 32]>,
+}
 
-    pub creator: Signer<'info>,
+/// Entry fee split in basis points; the three shares sum to 10_000.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct EntryFeeSplit {
+    pub creator_bps: u16,
+    pub community_bps: u16,
+    /// Share added to the stream's `bonus_pool` for winners.
+    pub prize_bps: u16,
 }
 
-#[derive(Accounts)]
-pub struct ClaimRefund<'info> {
-    #[account(
-        mut,
-        has_one = viewer,
-        constraint = prediction.stream == stream.key()
-    )]
-    pub prediction: Account<'info, Prediction>,
+impl EntryFeeSplit {
+    pub const SPACE: usize = 2 + 2 + 2;
+}
 
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+/// Points table for ranked markets.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RankPoints {
+    /// Points for a choice placed in its exact final position.
+    pub exact: u16,
+    /// Points for a choice that finished in the top `rank_depth` at another position.
+    pub in_top: u16,
+}
 
-    #[account(
-        mut,
-        seeds = [b"vault", stream.key().as_ref()],
-        bump = vault.bump,
-    )]
-    pub vault: Account<'info, TokenVault>,
+impl RankPoints {
+    pub const SPACE: usize = 2 + 2;
+}
 
-    #[account(
-        mut,
-        constraint = viewer_token_account.owner == viewer.key(),
-        constraint = viewer_token_account.mint == vault.mint
-    )]
-    pub viewer_token_account: Account<'info, TokenAccount>,
+/// One winning choice in a multi-winner resolution.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WinningChoice {
+    pub choice: u8,
+    /// Relative payout weight; `None` counts as 1.
+    pub weight: Option<u16>,
+}
 
-    #[account(
-        mut,
-        constraint = vault_token_account.key() == vault.token_account,
-        constraint = vault_token_account.mint == vault.mint
-    )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+/// Result posted by `resolve_outcome`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ResolutionOutcome {
+    Winners(Vec<WinningChoice>),
+    /// No valid outcome; every stake is refunded.
+    Void,
+}
 
-    pub viewer: Signer<'info>,
+/// Fixed-point scale for prediction weights, so inverse-distance scores keep precision.
+pub const WEIGHT_SCALE: u128 = 1_000_000;
 
-    pub token_program: Program<'info, Token>,
+/// Scoring rule for scalar markets.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScalarScoring {
+    /// Weight = stake / (1 + |predicted - actual| in whole units).
+    InverseDistance,
+    /// Only predictions within `band` (raw fixed-point units) of the result win, pro rata to stake.
+    WithinBand { band: u64 },
 }
 
-#[derive(Accounts)]
-pub struct EndStream<'info> {
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+impl ScalarScoring {
+    pub const SPACE: usize = 1 + 8; // variant tag + largest payload
+}
 
-    pub creator: Signer<'info>,
+#[account]
+pub struct Participant {
+    pub stream: Pubkey,
+    pub viewer: Pubkey,
+    pub stake_amount: u64,
+    // Cumulative stake limit from the allowlist (0 = no cap)
+    pub stake_cap: u64,
+    pub stake_by_choice: [u64; 11],
+    pub joined_at: i64,
+    pub allowlist_root: [u8; 32], // root the viewer was admitted under (zero = public)
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-pub struct ClaimReward<'info> {
-    /// Prediction account being claimed. Enforce that the prediction belongs to
-    /// the supplied stream and viewer via Anchor constraints.
-    #[account(
-        mut,
-        // ensure the prediction's viewer matches the signer
-        has_one = viewer,
-        // ensure the prediction's stream matches the provided stream account
-        constraint = prediction.stream == stream.key()
-    )]
-    pub prediction: Account<'info, Prediction>,
+impl Participant {
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // stream
+        32 + // viewer
+        8 + // stake_amount
+        8 + // stake_cap
+        (8 * 11) + // stake_by_choice
+        8 + // joined_at
+        32 + // allowlist_root
+        1; // bump
+}
 
-    /// The stream that has been resolved. Must match `prediction.stream`.
-    #[account(mut)]
-    pub stream: Account<'info, Stream>,
+#[account]
+pub struct Prediction {
+    pub stream: Pubkey,
+    pub viewer: Pubkey,
+    pub choice: u8,
+    pub stake_amount: u64,
+    pub timestamp: i64,
+    pub reward_claimed: bool,
+    pub refunded: bool,
+    pub scalar_value: i64,             // predicted value (scalar markets)
+    pub ranking: [u8; MAX_RANK_DEPTH], // predicted order (ranked markets)
+    pub weight: u128,                  // settlement weight set by `tally_prediction`
+    pub tallied: bool,
+    pub bump: u8,
+}
 
-    #[account(
-        mut,
-        seeds = [b"vault", stream.key().as_ref()],
-        bump = vault.bump,
-    )]
-    pub vault: Account<'info, TokenVault>,
+impl Prediction {
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // stream
+        32 + // viewer
+        1 + // choice
+        8 + // stake_amount
+        8 + // timestamp
+        1 + // reward_claimed
+        1 + // refunded
+        8 + // scalar_value
+        MAX_RANK_DEPTH + // ranking
+        16 + // weight
+        1 + // tallied
+        1; // bump
+}
 
-    #[account(
-        mut,
-        constraint = viewer_token_account.owner == viewer.key(),
-        constraint = viewer_token_account.mint == vault.mint
-    )]
-    pub viewer_token_account: Account<'info, TokenAccount>,
+#[account]
+pub struct LmsrPosition {
+    pub stream: Pubkey,
+    pub viewer: Pubkey,
+    pub shares: [u64; 11],
+    pub cost_basis: u64,
+    pub redeemed: bool,
+    pub bump: u8,
+}
 
-    #[account(
-        mut,
-        constraint = vault_token_account.key() == vault.token_account,
-        constraint = vault_token_account.mint == vault.mint
-    )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+impl LmsrPosition {
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // stream
+        32 + // viewer
+        (8 * 11) + // shares
+        8 + // cost_basis
+        1 + // redeemed
+        1; // bump
+}
 
-    /// The viewer (caller) claiming the reward. Must match `prediction.viewer`.
-    pub viewer: Signer<'info>,
+#[account]
+pub struct Challenge {
+    pub stream: Pubkey,
+    pub challenger: Pubkey,
+    pub challenge_id: u64,
+    pub counterparty: Pubkey, // default = open to anyone
+    pub acceptor: Pubkey,     // default = not yet accepted
+    pub choice: u8,           // challenger's side; acceptor takes every other outcome
+    pub stake_amount: u64,    // per side
+    pub created_at: i64,
+    pub accepted_at: i64,
+    pub bump: u8,
+}
+
+impl Challenge {
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // stream
+        32 + // challenger
+        8 + // challenge_id
+        32 + // counterparty
+        32 + // acceptor
+        1 + // choice
+        8 + // stake_amount
+        8 + // created_at
+        8 + // accepted_at
+        1; // bump
+}
+
+#[account]
+pub struct ParlayPool {
+    pub stream: Pubkey,
+    pub total_staked: u64,  // live (non-void) parlay stakes
+    pub winning_stake: u64, // stake of parlays settled as won
+    pub parlay_count: u64,
+    pub settled_count: u64,
+    pub bump: u8,
+}
+
+impl ParlayPool {
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // stream
+        8 + // total_staked
+        8 + // winning_stake
+        8 + // parlay_count
+        8 + // settled_count
+        1; // bump
+}
+
+#[account]
+pub struct Parlay {
+    pub pool: Pubkey,
+    pub bettor: Pubkey,
+    pub parlay_id: u64,
+    pub legs: [ParlayLeg; MAX_PARLAY_LEGS],
+    pub leg_count: u8,
+    pub stake_amount: u64,
+    pub status: ParlayStatus,
+    pub claimed: bool,
+    pub bump: u8,
+}
+
+impl Parlay {
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // pool
+        32 + // bettor
+        8 + // parlay_id
+        (ParlayLeg::SPACE * MAX_PARLAY_LEGS) + // legs
+        1 + // leg_count
+        8 + // stake_amount
+        1 + // status
+        1 + // claimed
+        1; // bump
+}
+
+/// One leg of a parlay: the stream and the choice it must resolve to.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ParlayLeg {
+    pub stream: Pubkey,
+    pub choice: u8,
+}
+
+impl ParlayLeg {
+    pub const SPACE: usize = 32 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParlayStatus {
+    Open,
+    Won,
+    Lost,
+    /// A leg was canceled or voided; the stake is refunded.
+    Void,
+}
+
+#[account]
+pub struct LiquiditySeed {
+    pub stream: Pubkey,
+    pub seeder: Pubkey,
+    pub choice: u8,
+    pub amount: u64,
+    pub claimed: bool,
+    pub bump: u8,
+}
 
-    pub token_program: Program<'info, Token>,
+impl LiquiditySeed {
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // stream
+        32 + // seeder
+        1 + // choice
+        8 + // amount
+        1 + // claimed
+        1; // bump
 }
 
 #[account]
-pub struct Stream {
-    pub creator: Pubkey,
-    pub stream_id: u64,
-    pub title: String,
-    pub start_time: i64,
-    pub end_time: i64,
-    // Phase 2.5 config fields
-    pub lock_offset_secs: i64,
-    pub grace_period_secs: i64,
-    pub tip_bps: u16,  // 0..=10_000
-    pub precision: u8, // <= 9
-    pub config_hash: [u8; 32],
-    // Aggregates
-    pub total_stake: u64,
-    pub total_by_choice: [u64; 11],
-    // Legacy flags (kept for compatibility while introducing state machine)
-    pub is_active: bool,
-    pub is_resolved: bool,
-    pub winning_choice: u8,
-    // Phase 2.5 runtime fields
-    pub tip_amount: u64,
-    pub resolved_at: i64,
-    pub canceled_at: i64,
+pub struct Sponsorship {
+    pub stream: Pubkey,
+    pub sponsor: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    // Hash of the off-chain brand metadata URI
+    pub metadata_uri_hash: [u8; 32],
+    pub created_at: i64,
+    pub reclaimed: bool,
     pub bump: u8,
 }
 
-impl Stream {
+impl Sponsorship {
     pub const SPACE: usize = DISCRIMINATOR +
-        32 + // creator
-        8 + // stream_id
-        4 + 200 + // title (max 200 chars)
-        8 + // start_time
-        8 + // end_time
-        8 + // lock_offset_secs
-        8 + // grace_period_secs
-        2 + // tip_bps
-        1 + // precision
-        32 + // config_hash
-        8 + // total_stake
-        (8 * 11) + // total_by_choice array (11 choices: 0..=10)
-        1 + // is_active
-        1 + // is_resolved
-        1 + // winning_choice (u8)
-        8 + // tip_amount
-        8 + // resolved_at
-        8 + // canceled_at
+        32 + // stream
+        32 + // sponsor
+        8 + // index
+        8 + // amount
+        32 + // metadata_uri_hash
+        8 + // created_at
+        1 + // reclaimed
         1; // bump
 }
 
 #[account]
-pub struct Participant {
+pub struct AccessPass {
     pub stream: Pubkey,
     pub viewer: Pubkey,
-    pub stake_amount: u64,
-    pub joined_at: i64,
+    pub amount_paid: u64,
+    // Portion of the fee added to the stream's bonus pool
+    pub prize_amount: u64,
+    pub purchased_at: i64,
+    pub refunded: bool,
     pub bump: u8,
 }
 
-impl Participant {
+impl AccessPass {
     pub const SPACE: usize = DISCRIMINATOR +
         32 + // stream
         32 + // viewer
-        8 + // stake_amount
-        8 + // joined_at
+        8 + // amount_paid
+        8 + // prize_amount
+        8 + // purchased_at
+        1 + // refunded
         1; // bump
 }
 
 #[account]
-pub struct Prediction {
+pub struct CreatorProfile {
+    pub creator: Pubkey,
+    // Handle claimed in the registry (empty = none) and off-chain profile metadata
+    pub handle: String,
+    pub metadata_uri: String,
+    // ID the next `create_stream` allocates
+    pub next_stream_id: u64,
+    // Lifetime stats
+    pub total_streams: u64,
+    pub streams_resolved: u64,
+    pub streams_canceled: u64,
+    pub tips_earned: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl CreatorProfile {
+    pub const MAX_HANDLE_LEN: usize = validation::MAX_HANDLE_LEN;
+    pub const MAX_URI_LEN: usize = 200;
+
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // creator
+        4 + Self::MAX_HANDLE_LEN + // handle
+        4 + Self::MAX_URI_LEN + // metadata_uri
+        8 + // next_stream_id
+        8 + // total_streams
+        8 + // streams_resolved
+        8 + // streams_canceled
+        8 + // tips_earned
+        8 + // created_at
+        1; // bump
+}
+
+#[account]
+pub struct HandleRecord {
+    // Normalized handle, also the PDA seed
+    pub handle: String,
+    pub profile: Pubkey,
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey, // nominated by `transfer_handle` (default key = none)
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+impl HandleRecord {
+    pub const SPACE: usize = DISCRIMINATOR +
+        4 + validation::MAX_HANDLE_LEN + // handle
+        32 + // profile
+        32 + // owner
+        32 + // pending_owner
+        8 + // claimed_at
+        1; // bump
+}
+
+#[account]
+pub struct GlobalConfig {
+    pub admin: Pubkey,
+    // Normalized handles nobody may claim
+    pub reserved_handles: Vec<String>,
+    pub bump: u8,
+}
+
+impl GlobalConfig {
+    pub const MAX_RESERVED_HANDLES: usize = 32;
+
+    pub const SPACE: usize = DISCRIMINATOR +
+        32 + // admin
+        4 + Self::MAX_RESERVED_HANDLES * (4 + validation::MAX_HANDLE_LEN) + // reserved_handles
+        1; // bump
+}
+
+#[account]
+pub struct StreamMetadata {
     pub stream: Pubkey,
-    pub viewer: Pubkey,
-    pub choice: u8,
-    pub stake_amount: u64,
-    pub timestamp: i64,
-    pub reward_claimed: bool,
-    pub refunded: bool,
+    pub description: String,
+    pub category: StreamCategory,
+    pub tags: Vec<String>,
+    // Thumbnail or playback URI
+    pub media_uri: String,
+    // Hash of the off-chain content the URI points to
+    pub content_hash: [u8; 32],
     pub bump: u8,
 }
 
-impl Prediction {
+impl StreamMetadata {
+    pub const MAX_DESCRIPTION_LEN: usize = 1000;
+    pub const MAX_TAGS: usize = 8;
+    pub const MAX_TAG_LEN: usize = 32;
+    pub const MAX_URI_LEN: usize = 200;
+
+    /// Account size for the given variable-length fields; the account is resized to
+    /// this on every update.
+    pub fn space(description: &str, tags: &[String], media_uri: &str) -> usize {
+        DISCRIMINATOR +
+            32 + // stream
+            4 + description.len() + // description
+            1 + // category
+            4 + tags.iter().map(|tag| 4 + tag.len()).sum::<usize>() + // tags
+            4 + media_uri.len() + // media_uri
+            32 + // content_hash
+            1 // bump
+    }
+
+    pub fn validate(description: &str, tags: &[String], media_uri: &str) -> Result<()> {
+        require!(
+            description.len() <= Self::MAX_DESCRIPTION_LEN,
+            CypherCastError::DescriptionTooLong
+        );
+        require!(tags.len() <= Self::MAX_TAGS, CypherCastError::TooManyTags);
+        require!(
+            tags.iter()
+                .all(|tag| !tag.is_empty() && tag.len() <= Self::MAX_TAG_LEN),
+            CypherCastError::InvalidTag
+        );
+        require!(
+            media_uri.len() <= Self::MAX_URI_LEN,
+            CypherCastError::UriTooLong
+        );
+        Ok(())
+    }
+}
+
+#[account]
+pub struct StreamRoles {
+    pub stream: Pubkey,
+    // Wallets allowed to lock, pause, resume and end the stream
+    pub moderators: Vec<Pubkey>,
+    // Wallets allowed to resolve the stream
+    pub resolvers: Vec<Pubkey>,
+    // Nominated by `transfer_ownership` (default key = none)
+    pub pending_owner: Pubkey,
+    pub bump: u8,
+}
+
+impl StreamRoles {
+    pub const MAX_MEMBERS: usize = 8;
+
     pub const SPACE: usize = DISCRIMINATOR +
         32 + // stream
-        32 + // viewer
-        1 + // choice
-        8 + // stake_amount
-        8 + // timestamp
-        1 + // reward_claimed
-        1 + // refunded
+        4 + (32 * Self::MAX_MEMBERS) + // moderators
+        4 + (32 * Self::MAX_MEMBERS) + // resolvers
+        32 + // pending_owner
         1; // bump
+
+    pub fn members(&self, role: StreamRole) -> &Vec<Pubkey> {
+        match role {
+            StreamRole::Moderator => &self.moderators,
+            StreamRole::Resolver => &self.resolvers,
+        }
+    }
+
+    pub fn members_mut(&mut self, role: StreamRole) -> &mut Vec<Pubkey> {
+        match role {
+            StreamRole::Moderator => &mut self.moderators,
+            StreamRole::Resolver => &mut self.resolvers,
+        }
+    }
 }
 
 #[account]
@@ -905,6 +7068,16 @@ pub struct RewardClaimed {
     pub amount: u64,
 }
 
+#[event]
+pub struct SharesTraded {
+    pub stream: Pubkey,
+    pub viewer: Pubkey,
+    pub choice: u8,
+    pub shares: u64,
+    pub amount: u64,
+    pub buy: bool,
+}
+
 #[event]
 pub struct CommunityVaultInitialized {
     pub authority: Pubkey,
@@ -912,6 +7085,212 @@ pub struct CommunityVaultInitialized {
     pub token_account: Pubkey,
 }
 
+#[event]
+pub struct ChallengeOpened {
+    pub stream: Pubkey,
+    pub challenge: Pubkey,
+    pub challenger: Pubkey,
+    pub counterparty: Pubkey,
+    pub choice: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ChallengeAccepted {
+    pub stream: Pubkey,
+    pub challenge: Pubkey,
+    pub acceptor: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ChallengeSettled {
+    pub stream: Pubkey,
+    pub challenge: Pubkey,
+    pub challenger_payout: u64,
+    pub acceptor_payout: u64,
+}
+
+#[event]
+pub struct ScalarPredictionSubmitted {
+    pub stream: Pubkey,
+    pub viewer: Pubkey,
+    pub value: i64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ScalarResolved {
+    pub stream: Pubkey,
+    pub actual_value: i64,
+    pub tip_amount: u64,
+}
+
+#[event]
+pub struct OutcomeResolved {
+    pub stream: Pubkey,
+    pub winner_weights: [u16; 11],
+    pub is_void: bool,
+    pub tip_amount: u64,
+}
+
+#[event]
+pub struct RankedPredictionSubmitted {
+    pub stream: Pubkey,
+    pub viewer: Pubkey,
+    pub ranking: [u8; MAX_RANK_DEPTH],
+    pub amount: u64,
+}
+
+#[event]
+pub struct RankingResolved {
+    pub stream: Pubkey,
+    pub ranking: [u8; MAX_RANK_DEPTH],
+    pub tip_amount: u64,
+}
+
+#[event]
+pub struct ParlayPlaced {
+    pub pool: Pubkey,
+    pub parlay: Pubkey,
+    pub bettor: Pubkey,
+    pub leg_count: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ParlaySettled {
+    pub pool: Pubkey,
+    pub parlay: Pubkey,
+    pub status: ParlayStatus,
+}
+
+#[event]
+pub struct LiquiditySeeded {
+    pub stream: Pubkey,
+    pub seeder: Pubkey,
+    pub choice: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SponsorshipAdded {
+    pub stream: Pubkey,
+    pub sponsor: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub metadata_uri_hash: [u8; 32],
+}
+
+#[event]
+pub struct FreePredictionSubmitted {
+    pub stream: Pubkey,
+    pub viewer: Pubkey,
+    pub choice: u8,
+    pub weight: u64,
+}
+
+#[event]
+pub struct LockFinalized {
+    pub stream: Pubkey,
+    pub prediction_count: u64,
+    pub total_stake: u64,
+    pub threshold_met: bool,
+}
+
+#[event]
+pub struct PredictionsLocked {
+    pub stream: Pubkey,
+    pub authority: Pubkey,
+    pub locked_at: i64,
+}
+
+#[event]
+pub struct PredictionsPaused {
+    pub stream: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PredictionsResumed {
+    pub stream: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamRescheduled {
+    pub stream: Pubkey,
+    pub old_start_time: i64,
+    pub new_start_time: i64,
+    pub old_lock_time: i64,
+    pub new_lock_time: i64,
+    pub withdrawal_deadline: i64,
+}
+
+#[event]
+pub struct BondPosted {
+    pub stream: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BondSlashed {
+    pub stream: Pubkey,
+    pub amount: u64,
+    pub target: BondSlashTarget,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub stream: Pubkey,
+    pub role: StreamRole,
+    pub member: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub stream: Pubkey,
+    pub role: StreamRole,
+    pub member: Pubkey,
+}
+
+#[event]
+pub struct OwnershipTransferred {
+    pub stream: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct HandleClaimed {
+    pub handle: String,
+    pub profile: Pubkey,
+    pub owner: Pubkey,
+}
+
+impl From<validation::ValidationError> for CypherCastError {
+    fn from(err: validation::ValidationError) -> Self {
+        use validation::ValidationError::*;
+        match err {
+            StartTimeInPast => CypherCastError::StartTimeInPast,
+            InvalidLockOffset => CypherCastError::InvalidLockOffset,
+            InvalidGracePeriod => CypherCastError::InvalidGracePeriod,
+            InvalidObservationWindow | InvalidStakeLimits => CypherCastError::InvalidConfig,
+            HandleTooLong => CypherCastError::HandleTooLong,
+            InvalidHandle => CypherCastError::InvalidHandle,
+        }
+    }
+}
+
+impl From<validation::ValidationError> for Error {
+    fn from(err: validation::ValidationError) -> Self {
+        CypherCastError::from(err).into()
+    }
+}
+
 #[error_code]
 pub enum CypherCastError {
     #[msg("Stream is not active")]
@@ -950,4 +7329,122 @@ pub enum CypherCastError {
     RefundAlreadyClaimed,
     #[msg("Stream already activated")]
     AlreadyActivated,
+    #[msg("Invalid fixed odds")]
+    InvalidOdds,
+    #[msg("Operation not supported in this market mode")]
+    UnsupportedMarketMode,
+    #[msg("Stake would exceed the house reserve")]
+    ReserveExceeded,
+    #[msg("House reserve already settled")]
+    ReserveAlreadySettled,
+    #[msg("Price moved beyond the slippage limit")]
+    SlippageExceeded,
+    #[msg("Not enough shares in position")]
+    InsufficientShares,
+    #[msg("Invalid challenge counterparty")]
+    InvalidCounterparty,
+    #[msg("Challenge already accepted")]
+    ChallengeAlreadyAccepted,
+    #[msg("Challenge not accepted")]
+    ChallengeNotAccepted,
+    #[msg("Value outside the market range")]
+    ValueOutOfRange,
+    #[msg("Prediction already tallied")]
+    AlreadyTallied,
+    #[msg("Not all predictions have been tallied")]
+    TallyIncomplete,
+    #[msg("Stream was resolved as void")]
+    StreamVoided,
+    #[msg("Winning choice listed more than once")]
+    DuplicateWinner,
+    #[msg("Invalid payout weight")]
+    InvalidWeight,
+    #[msg("Invalid ranking")]
+    InvalidRanking,
+    #[msg("Invalid parlay legs")]
+    InvalidParlay,
+    #[msg("Parlay already settled")]
+    ParlayAlreadySettled,
+    #[msg("Viewer does not meet the stream's entry gate")]
+    GateNotSatisfied,
+    #[msg("Missing or invalid attestation signature")]
+    InvalidAttestation,
+    #[msg("Entry fee split must sum to 10000 bps")]
+    InvalidFeeSplit,
+    #[msg("A paid access pass is required for this stream")]
+    AccessPassRequired,
+    #[msg("Entry fees already distributed")]
+    EntryFeesAlreadyDistributed,
+    #[msg("Viewer is not on the stream allowlist")]
+    NotAllowlisted,
+    #[msg("Stake exceeds the viewer's allowlisted cap")]
+    AllowlistCapExceeded,
+    #[msg("Stake is below the stream minimum")]
+    BelowMinStake,
+    #[msg("Stake exceeds the per-viewer cap")]
+    ViewerStakeCapExceeded,
+    #[msg("Stake would exceed the allowed share of the choice pool")]
+    ChoiceShareExceeded,
+    #[msg("Participant account required for this stream")]
+    ParticipantRequired,
+    #[msg("Stream has not reached its lock time")]
+    StreamNotLocked,
+    #[msg("Lock already finalized")]
+    LockAlreadyFinalized,
+    #[msg("Participation threshold not finalized; call finalize_lock first")]
+    LockNotFinalized,
+    #[msg("Predictions are paused")]
+    PredictionsPaused,
+    #[msg("Predictions are not paused")]
+    PredictionsNotPaused,
+    #[msg("Role member list is full")]
+    TooManyRoleMembers,
+    #[msg("Lock extension exceeds the configured maximum")]
+    LockExtensionTooLong,
+    #[msg("No withdrawal window is open for this prediction")]
+    WithdrawalWindowClosed,
+    #[msg("Start time is too far in the past")]
+    StartTimeInPast,
+    #[msg("Lock offset is out of bounds or the lock time has already passed")]
+    InvalidLockOffset,
+    #[msg("Grace period must be between zero and the program maximum")]
+    InvalidGracePeriod,
+    #[msg("Outcome cannot be resolved until the observation window after lock has passed")]
+    ObservationWindowOpen,
+    #[msg("Creator bond must be posted before activation")]
+    BondNotPosted,
+    #[msg("Creator bond already posted")]
+    BondAlreadyPosted,
+    #[msg("Cancellation after lock requires the arbiter or a posted creator bond")]
+    LateCancelRequiresBond,
+    #[msg("Creator bond was slashed")]
+    BondSlashed,
+    #[msg("Creator bond was not slashed to the community vault")]
+    BondNotSlashed,
+    #[msg("Creator bond already released")]
+    BondAlreadyReleased,
+    #[msg("Handle too long")]
+    HandleTooLong,
+    #[msg("URI too long")]
+    UriTooLong,
+    #[msg("Handles may only use a-z, 0-9 and _, and must be normalized")]
+    InvalidHandle,
+    #[msg("Handle is reserved")]
+    HandleReserved,
+    #[msg("Creator profile already holds a handle")]
+    ProfileHasHandle,
+    #[msg("Reserved handle list is full")]
+    TooManyReservedHandles,
+    #[msg("Description too long")]
+    DescriptionTooLong,
+    #[msg("Too many tags")]
+    TooManyTags,
+    #[msg("Tags must be non-empty and at most 32 bytes")]
+    InvalidTag,
+    #[msg("Stream metadata account required")]
+    MetadataRequired,
+    #[msg("Refunds sharing the slashed bond are still outstanding")]
+    RefundsOutstanding,
+    #[msg("Sponsorship is still owed to winners")]
+    SponsorshipInUse,
 }
@@ -0,0 +1,222 @@
+//! Fixed-point math for the logarithmic market scoring rule (LMSR).
+//!
+//! The cost function is `C(q) = b * ln(sum_i exp(q_i / b))`, evaluated with the
+//! log-sum-exp shift `C(q) = m + b * ln(sum_i exp((q_i - m) / b))` where
+//! `m = max(q)`, so every exponent is non-positive and never overflows.
+//! All intermediate values are `u128` scaled by [`SCALE`].
+
+/// Fixed-point scale (1e12).
+pub const SCALE: u128 = 1_000_000_000_000;
+
+/// ln(2) scaled by [`SCALE`].
+const LN2: u128 = 693_147_180_560;
+
+/// Exponents beyond this (scaled) are treated as zero; e^-64 is far below 1 / SCALE.
+const EXP_CUTOFF: u128 = 64 * SCALE;
+
+/// Number of series terms used by `exp_neg` and `ln`.
+const SERIES_TERMS: u128 = 24;
+
+/// `e^(-x / SCALE) * SCALE` for `x >= 0`.
+pub fn exp_neg(x: u128) -> u128 {
+    if x >= EXP_CUTOFF {
+        return 0;
+    }
+    // x = k * ln2 + r with r in [0, ln2), so e^-x = e^-r / 2^k
+    let k = x / LN2;
+    let r = x - k * LN2;
+
+    // Taylor series for e^r; all terms positive, r < 0.7
+    let mut term = SCALE;
+    let mut sum = SCALE;
+    for n in 1..SERIES_TERMS {
+        term = term * r / SCALE / n;
+        if term == 0 {
+            break;
+        }
+        sum += term;
+    }
+
+    (SCALE * SCALE / sum) >> k
+}
+
+/// `ln(x / SCALE) * SCALE` for `x >= SCALE`. Returns `None` below 1.0.
+pub fn ln(x: u128) -> Option<u128> {
+    if x < SCALE {
+        return None;
+    }
+    // x = 2^k * m with m in [1, 2)
+    let mut k: u128 = 0;
+    let mut m = x;
+    while m >= 2 * SCALE {
+        m >>= 1;
+        k += 1;
+    }
+
+    // ln(m) = 2 * atanh(z), z = (m - 1) / (m + 1) in [0, 1/3)
+    let z = (m - SCALE) * SCALE / (m + SCALE);
+    let z2 = z * z / SCALE;
+    let mut power = z;
+    let mut sum = 0u128;
+    let mut n = 1u128;
+    while n < 2 * SERIES_TERMS && power > 0 {
+        sum += power / n;
+        power = power * z2 / SCALE;
+        n += 2;
+    }
+
+    Some(k * LN2 + 2 * sum)
+}
+
+/// LMSR cost `C(q)` in token units, scaled by [`SCALE`].
+pub fn cost(quantities: &[u64], b: u64) -> Option<u128> {
+    if b == 0 || quantities.is_empty() {
+        return None;
+    }
+    let max = *quantities.iter().max()?;
+    let mut sum: u128 = 0;
+    for &q in quantities {
+        let shift = ((max - q) as u128).checked_mul(SCALE)? / b as u128;
+        sum = sum.checked_add(exp_neg(shift))?;
+    }
+    (max as u128)
+        .checked_mul(SCALE)?
+        .checked_add((b as u128).checked_mul(ln(sum)?)?)
+}
+
+/// Cost of buying `shares` of `choice`, rounded up in favour of the market.
+pub fn buy_cost(quantities: &[u64], b: u64, choice: usize, shares: u64) -> Option<u64> {
+    let before = cost(quantities, b)?;
+    let mut after_q = quantities.to_vec();
+    after_q[choice] = after_q[choice].checked_add(shares)?;
+    let after = cost(&after_q, b)?;
+    let delta = after.checked_sub(before)?;
+    u64::try_from(delta.div_ceil(SCALE)).ok()
+}
+
+/// Proceeds of selling `shares` of `choice`, rounded down in favour of the market.
+pub fn sell_proceeds(quantities: &[u64], b: u64, choice: usize, shares: u64) -> Option<u64> {
+    let before = cost(quantities, b)?;
+    let mut after_q = quantities.to_vec();
+    after_q[choice] = after_q[choice].checked_sub(shares)?;
+    let after = cost(&after_q, b)?;
+    u64::try_from(before.saturating_sub(after) / SCALE).ok()
+}
+
+/// Worst-case market maker loss `b * ln(n)`, rounded up. This is the subsidy
+/// the creator must fund before trading opens.
+pub fn max_loss(b: u64, outcomes: u8) -> Option<u64> {
+    let ln_n = ln((outcomes as u128).checked_mul(SCALE)?)?;
+    u64::try_from((b as u128).checked_mul(ln_n)?.div_ceil(SCALE)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exp_neg_is_decreasing() {
+        assert_eq!(exp_neg(0), SCALE);
+        let mut previous = exp_neg(0);
+        for step in 1..200u128 {
+            let value = exp_neg(step * SCALE / 4);
+            assert!(value <= previous);
+            previous = value;
+        }
+        assert_eq!(exp_neg(EXP_CUTOFF), 0);
+        assert_eq!(exp_neg(u128::MAX), 0);
+    }
+
+    #[test]
+    fn exp_neg_matches_known_values() {
+        // e^-1 = 0.367879441171...
+        let e_inv = exp_neg(SCALE);
+        assert!(e_inv.abs_diff(367_879_441_171) < 1_000);
+    }
+
+    #[test]
+    fn ln_is_increasing_and_inverts_exp() {
+        assert_eq!(ln(SCALE), Some(0));
+        assert_eq!(ln(SCALE - 1), None);
+        let mut previous = 0;
+        for step in 1..200u128 {
+            let value = ln(SCALE + step * SCALE / 8).unwrap();
+            assert!(value >= previous);
+            previous = value;
+        }
+        // ln(1 / e^-x) = x
+        let x = 3 * SCALE;
+        let back = ln(SCALE * SCALE / exp_neg(x)).unwrap();
+        assert!(back.abs_diff(x) < 1_000);
+        assert!(ln(u128::MAX).is_some());
+    }
+
+    #[test]
+    fn cost_is_increasing_in_each_quantity() {
+        let b = 1_000;
+        let mut previous = cost(&[0, 0], b).unwrap();
+        for q in (100..5_000).step_by(100) {
+            let value = cost(&[q, 0], b).unwrap();
+            assert!(value > previous);
+            previous = value;
+        }
+    }
+
+    #[test]
+    fn buy_cost_is_increasing_in_shares() {
+        let q = [500, 200, 0];
+        let mut previous = 0;
+        for shares in (1..2_000).step_by(50) {
+            let value = buy_cost(&q, 1_000, 1, shares).unwrap();
+            assert!(value >= previous);
+            previous = value;
+        }
+    }
+
+    #[test]
+    fn round_trip_never_pays_back_more_than_was_paid() {
+        let b = 1_000;
+        for start in [[0u64, 0], [3_000, 0], [0, 3_000], [1_234, 987]] {
+            for shares in [1u64, 7, 100, 999, 10_000] {
+                let paid = buy_cost(&start, b, 0, shares).unwrap();
+                let mut after = start;
+                after[0] += shares;
+                let back = sell_proceeds(&after, b, 0, shares).unwrap();
+                assert!(back <= paid, "{start:?} {shares}: {back} > {paid}");
+            }
+        }
+    }
+
+    #[test]
+    fn market_maker_loss_is_bounded_by_max_loss() {
+        let b = 1_000;
+        for outcomes in 2..=10u8 {
+            let bound = max_loss(b, outcomes).unwrap() as u128 * SCALE;
+            let empty = vec![0u64; outcomes as usize];
+            let base = cost(&empty, b).unwrap();
+            for winner_shares in [0u64, 10, 1_000, 50_000, 1_000_000] {
+                let mut q = empty.clone();
+                q[0] = winner_shares;
+                let collected = cost(&q, b).unwrap() - base;
+                let payout = winner_shares as u128 * SCALE;
+                assert!(payout.saturating_sub(collected) <= bound);
+            }
+        }
+        // b * ln(2) for b = 1000 is 693.1..., rounded up
+        assert_eq!(max_loss(1_000, 2), Some(694));
+    }
+
+    #[test]
+    fn limits_return_none_instead_of_overflowing() {
+        assert_eq!(cost(&[], 1_000), None);
+        assert_eq!(cost(&[1, 2], 0), None);
+        assert_eq!(buy_cost(&[u64::MAX, 0], 1_000, 0, 1), None);
+        assert_eq!(sell_proceeds(&[0, 0], 1_000, 0, 1), None);
+        assert!(cost(&[u64::MAX, 0], 1).is_some());
+        assert!(cost(&[u64::MAX, u64::MAX], u64::MAX).is_some());
+        assert!(max_loss(u64::MAX, 2).is_some());
+        // b * ln(255) no longer fits in a u64
+        assert_eq!(max_loss(u64::MAX, u8::MAX), None);
+        assert_eq!(max_loss(1_000, 0), None);
+    }
+}
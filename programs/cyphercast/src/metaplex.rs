@@ -0,0 +1,105 @@
+//! Minimal reader for Metaplex Token Metadata accounts.
+//!
+//! Only the fields needed to check collection membership are decoded, so the
+//! program does not depend on the `mpl-token-metadata` crate. The layout is the
+//! Borsh encoding of `Metadata` (key, update authority, mint, data, flags,
+//! edition nonce, token standard, collection).
+
+use anchor_lang::prelude::Pubkey;
+
+/// Metaplex Token Metadata program.
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    anchor_lang::solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// `Key::MetadataV1` discriminant.
+const KEY_METADATA_V1: u8 = 4;
+
+/// Size of a serialized `Creator` (address, verified, share).
+const CREATOR_LEN: usize = 32 + 1 + 1;
+
+/// Fields of a metadata account relevant to collection gating.
+pub struct MetadataCollection {
+    pub mint: Pubkey,
+    /// Collection key, only when the collection is verified.
+    pub verified_collection: Option<Pubkey>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        self.take(32).and_then(|b| Pubkey::try_from(b).ok())
+    }
+
+    fn string(&mut self) -> Option<()> {
+        let len = self.u32()? as usize;
+        self.take(len).map(|_| ())
+    }
+
+    /// Skip an `Option<T>` whose payload is `len` bytes.
+    fn option(&mut self, len: usize) -> Option<bool> {
+        match self.u8()? {
+            0 => Some(false),
+            1 => self.take(len).map(|_| true),
+            _ => None,
+        }
+    }
+}
+
+/// Decode the mint and verified collection from raw metadata account data.
+/// Returns `None` if the data is not a `MetadataV1` account.
+pub fn read_collection(data: &[u8]) -> Option<MetadataCollection> {
+    let mut r = Reader { data, pos: 0 };
+    if r.u8()? != KEY_METADATA_V1 {
+        return None;
+    }
+    r.take(32)?; // update_authority
+    let mint = r.pubkey()?;
+
+    // Data { name, symbol, uri, seller_fee_basis_points, creators }
+    r.string()?;
+    r.string()?;
+    r.string()?;
+    r.take(2)?;
+    if r.option(0)? {
+        let creators = r.u32()? as usize;
+        r.take(creators.checked_mul(CREATOR_LEN)?)?;
+    }
+
+    r.take(2)?; // primary_sale_happened, is_mutable
+    r.option(1)?; // edition_nonce
+    r.option(1)?; // token_standard
+
+    // Option<Collection { verified, key }>
+    let verified_collection = match r.u8()? {
+        1 => {
+            let verified = r.u8()? == 1;
+            let key = r.pubkey()?;
+            verified.then_some(key)
+        }
+        _ => None,
+    };
+
+    Some(MetadataCollection {
+        mint,
+        verified_collection,
+    })
+}
@@ -0,0 +1,322 @@
+//! Pure validation of stream timing parameters, stake limits and creator handles.
+//!
+//! The program runs these checks against `Clock` in `create_stream`,
+//! `reschedule_stream` and `configure_observation_window`, checks stake limits in
+//! `configure_stake_limits` and on every stake, and normalizes handles in the
+//! handle registry; clients can call the same functions to pre-check a form
+//! before sending a transaction.
+//!
+//! The module only depends on `core`/`std`, so clients can compile it on its own
+//! (e.g. through `#[path]`) without pulling in Anchor. The program maps
+//! [`ValidationError`] onto its `ValidationError` codes.
+
+use std::fmt;
+
+/// Why a timing parameter or handle was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    StartTimeInPast,
+    InvalidLockOffset,
+    InvalidGracePeriod,
+    InvalidObservationWindow,
+    InvalidStakeLimits,
+    HandleTooLong,
+    InvalidHandle,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::StartTimeInPast => "Start time is in the past",
+            Self::InvalidLockOffset => "Lock offset out of range",
+            Self::InvalidGracePeriod => "Grace period out of range",
+            Self::InvalidObservationWindow => "Observation window out of range",
+            Self::InvalidStakeLimits => "Stake limits are inconsistent or unreachable",
+            Self::HandleTooLong => "Handle too long",
+            Self::InvalidHandle => "Handle must be 3 or more characters of a-z, 0-9 or _",
+        })
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// How far in the past `start_time` may be, to tolerate client clock skew.
+pub const MAX_START_SKEW_SECS: i64 = 5 * 60;
+
+/// Smallest lock offset: predictions must stay open for at least a second.
+pub const MIN_LOCK_OFFSET_SECS: i64 = 1;
+
+/// Largest lock offset (one year).
+pub const MAX_LOCK_OFFSET_SECS: i64 = 365 * 24 * 60 * 60;
+
+/// Largest grace period (seven days).
+pub const MAX_GRACE_PERIOD_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Largest observation window between lock and resolution (thirty days).
+pub const MAX_OBSERVATION_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// `start_time` may not be more than [`MAX_START_SKEW_SECS`] before `now`.
+pub fn validate_start_time(now: i64, start_time: i64) -> Result<(), ValidationError> {
+    if start_time < now.saturating_sub(MAX_START_SKEW_SECS) {
+        return Err(ValidationError::StartTimeInPast);
+    }
+    Ok(())
+}
+
+/// The lock offset must be within bounds and the resulting lock still in the future,
+/// so a stream can never be locked from birth.
+pub fn validate_lock_offset(
+    now: i64,
+    start_time: i64,
+    lock_offset_secs: i64,
+) -> Result<(), ValidationError> {
+    if !(MIN_LOCK_OFFSET_SECS..=MAX_LOCK_OFFSET_SECS).contains(&lock_offset_secs) {
+        return Err(ValidationError::InvalidLockOffset);
+    }
+    match start_time.checked_add(lock_offset_secs) {
+        Some(lock_time) if lock_time > now => Ok(()),
+        _ => Err(ValidationError::InvalidLockOffset),
+    }
+}
+
+/// The grace period must be non-negative and at most [`MAX_GRACE_PERIOD_SECS`].
+pub fn validate_grace_period(grace_period_secs: i64) -> Result<(), ValidationError> {
+    if !(0..=MAX_GRACE_PERIOD_SECS).contains(&grace_period_secs) {
+        return Err(ValidationError::InvalidGracePeriod);
+    }
+    Ok(())
+}
+
+/// The observation window must be non-negative and at most [`MAX_OBSERVATION_SECS`].
+pub fn validate_observation_window(min_observation_secs: i64) -> Result<(), ValidationError> {
+    if !(0..=MAX_OBSERVATION_SECS).contains(&min_observation_secs) {
+        return Err(ValidationError::InvalidObservationWindow);
+    }
+    Ok(())
+}
+
+/// Run every timing check for a new (or Draft) stream schedule.
+pub fn validate_schedule(
+    now: i64,
+    start_time: i64,
+    lock_offset_secs: i64,
+    grace_period_secs: i64,
+) -> Result<(), ValidationError> {
+    validate_start_time(now, start_time)?;
+    validate_lock_offset(now, start_time, lock_offset_secs)?;
+    validate_grace_period(grace_period_secs)
+}
+
+/// Check a stream's anti-whale limits (0 disables each). The per-viewer cap may not be
+/// below the minimum stake, and a share cap below 100% needs a pool to measure against:
+/// either `share_limit_min_pool` or, when `seedable`, liquidity seeded by the creator.
+/// Without one, the first stake into an empty pool would always hold all of it.
+pub fn validate_stake_limits(
+    min_stake: u64,
+    max_stake_per_viewer: u64,
+    max_share_bps: u16,
+    share_limit_min_pool: u64,
+    seedable: bool,
+) -> Result<(), ValidationError> {
+    if max_share_bps > 10_000 || (max_stake_per_viewer != 0 && max_stake_per_viewer < min_stake) {
+        return Err(ValidationError::InvalidStakeLimits);
+    }
+    if max_share_bps > 0 && max_share_bps < 10_000 && share_limit_min_pool == 0 && !seedable {
+        return Err(ValidationError::InvalidStakeLimits);
+    }
+    Ok(())
+}
+
+/// Whether a viewer holding `held_after` of a pool of `pool_after` (both including the new
+/// stake) stays within `max_share_bps`. The cap only applies once the pool holds at least
+/// `min_pool`, so an empty pool can take its first stakes.
+pub fn within_share_limit(
+    held_after: u64,
+    pool_after: u64,
+    max_share_bps: u16,
+    min_pool: u64,
+) -> bool {
+    max_share_bps == 0
+        || pool_after < min_pool
+        || held_after as u128 * 10_000 <= pool_after as u128 * max_share_bps as u128
+}
+
+/// Shortest creator handle.
+pub const MIN_HANDLE_LEN: usize = 3;
+
+/// Longest creator handle; it must fit in a single PDA seed.
+pub const MAX_HANDLE_LEN: usize = 32;
+
+/// Lowercase `handle` and check it only uses `a-z`, `0-9` and `_`, so visually
+/// identical handles cannot be registered twice.
+pub fn normalize_handle(handle: &str) -> Result<String, ValidationError> {
+    if handle.len() > MAX_HANDLE_LEN {
+        return Err(ValidationError::HandleTooLong);
+    }
+    let normalized = handle.to_ascii_lowercase();
+    let allowed = |c: u8| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'_';
+    if normalized.len() < MIN_HANDLE_LEN || !normalized.bytes().all(allowed) {
+        return Err(ValidationError::InvalidHandle);
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn start_time_skew_boundary() {
+        assert_eq!(validate_start_time(NOW, NOW - MAX_START_SKEW_SECS), Ok(()));
+        assert_eq!(
+            validate_start_time(NOW, NOW - MAX_START_SKEW_SECS - 1),
+            Err(ValidationError::StartTimeInPast)
+        );
+        assert_eq!(validate_start_time(NOW, i64::MAX), Ok(()));
+        assert_eq!(validate_start_time(i64::MIN, i64::MIN), Ok(()));
+    }
+
+    #[test]
+    fn lock_offset_bounds() {
+        assert_eq!(validate_lock_offset(NOW, NOW, MIN_LOCK_OFFSET_SECS), Ok(()));
+        assert_eq!(validate_lock_offset(NOW, NOW, MAX_LOCK_OFFSET_SECS), Ok(()));
+        for offset in [0, -1, MAX_LOCK_OFFSET_SECS + 1] {
+            assert_eq!(
+                validate_lock_offset(NOW, NOW, offset),
+                Err(ValidationError::InvalidLockOffset)
+            );
+        }
+    }
+
+    #[test]
+    fn lock_must_be_in_the_future() {
+        // Started in the past (within skew) with a lock that has already passed
+        assert_eq!(
+            validate_lock_offset(NOW, NOW - 60, 60),
+            Err(ValidationError::InvalidLockOffset)
+        );
+        assert_eq!(validate_lock_offset(NOW, NOW - 60, 61), Ok(()));
+        assert_eq!(
+            validate_lock_offset(NOW, i64::MAX, MIN_LOCK_OFFSET_SECS),
+            Err(ValidationError::InvalidLockOffset)
+        );
+    }
+
+    #[test]
+    fn grace_and_observation_bounds() {
+        assert_eq!(validate_grace_period(0), Ok(()));
+        assert_eq!(validate_grace_period(MAX_GRACE_PERIOD_SECS), Ok(()));
+        assert_eq!(
+            validate_grace_period(-1),
+            Err(ValidationError::InvalidGracePeriod)
+        );
+        assert_eq!(
+            validate_grace_period(MAX_GRACE_PERIOD_SECS + 1),
+            Err(ValidationError::InvalidGracePeriod)
+        );
+
+        assert_eq!(validate_observation_window(0), Ok(()));
+        assert_eq!(validate_observation_window(MAX_OBSERVATION_SECS), Ok(()));
+        assert_eq!(
+            validate_observation_window(-1),
+            Err(ValidationError::InvalidObservationWindow)
+        );
+        assert_eq!(
+            validate_observation_window(MAX_OBSERVATION_SECS + 1),
+            Err(ValidationError::InvalidObservationWindow)
+        );
+    }
+
+    #[test]
+    fn schedule_reports_first_failure() {
+        assert_eq!(validate_schedule(NOW, NOW, 3600, 600), Ok(()));
+        assert_eq!(
+            validate_schedule(NOW, NOW - MAX_START_SKEW_SECS - 1, 0, -1),
+            Err(ValidationError::StartTimeInPast)
+        );
+        assert_eq!(
+            validate_schedule(NOW, NOW, 3600, -1),
+            Err(ValidationError::InvalidGracePeriod)
+        );
+    }
+
+    #[test]
+    fn stake_limit_consistency() {
+        assert_eq!(validate_stake_limits(0, 0, 0, 0, false), Ok(()));
+        assert_eq!(validate_stake_limits(10, 10, 10_000, 0, false), Ok(()));
+        assert_eq!(
+            validate_stake_limits(10, 9, 0, 0, true),
+            Err(ValidationError::InvalidStakeLimits)
+        );
+        assert_eq!(
+            validate_stake_limits(0, 0, 10_001, 0, true),
+            Err(ValidationError::InvalidStakeLimits)
+        );
+    }
+
+    #[test]
+    fn share_cap_needs_a_pool_to_measure_against() {
+        // Scalar / ranked / fixed-odds pools cannot be seeded
+        assert_eq!(
+            validate_stake_limits(0, 0, 2_500, 0, false),
+            Err(ValidationError::InvalidStakeLimits)
+        );
+        assert_eq!(validate_stake_limits(0, 0, 2_500, 1_000, false), Ok(()));
+        assert_eq!(validate_stake_limits(0, 0, 2_500, 0, true), Ok(()));
+    }
+
+    #[test]
+    fn first_stake_into_empty_capped_pool() {
+        // Scalar stream, 25% cap, applied once the pool holds 1_000
+        assert!(within_share_limit(999, 999, 2_500, 1_000));
+        assert!(!within_share_limit(1_000, 1_000, 2_500, 1_000));
+        // Without a floor the first stake is always 100% of the pool
+        assert!(!within_share_limit(1, 1, 2_500, 0));
+        assert!(within_share_limit(1, 1, 10_000, 0));
+        assert!(within_share_limit(u64::MAX, u64::MAX, 0, 0));
+    }
+
+    #[test]
+    fn share_cap_above_the_floor() {
+        assert!(within_share_limit(250, 1_000, 2_500, 1_000));
+        assert!(!within_share_limit(251, 1_000, 2_500, 1_000));
+        assert!(within_share_limit(u64::MAX / 4, u64::MAX, 2_500, 1));
+    }
+
+    #[test]
+    fn handle_length_bounds() {
+        assert_eq!(normalize_handle("abc"), Ok("abc".to_string()));
+        assert_eq!(normalize_handle("ab"), Err(ValidationError::InvalidHandle));
+        assert_eq!(normalize_handle(""), Err(ValidationError::InvalidHandle));
+        let longest = "a".repeat(MAX_HANDLE_LEN);
+        assert_eq!(normalize_handle(&longest), Ok(longest.clone()));
+        assert_eq!(
+            normalize_handle(&"a".repeat(MAX_HANDLE_LEN + 1)),
+            Err(ValidationError::HandleTooLong)
+        );
+    }
+
+    #[test]
+    fn handle_characters() {
+        assert_eq!(
+            normalize_handle("Cypher_Cast9"),
+            Ok("cypher_cast9".to_string())
+        );
+        for bad in [
+            "with space",
+            "dash-ed",
+            "dot.ted",
+            "émile",
+            "tab\there",
+            "ＡＢＣ",
+        ] {
+            assert_eq!(
+                normalize_handle(bad),
+                Err(ValidationError::InvalidHandle),
+                "{bad}"
+            );
+        }
+    }
+}
@@ -8,6 +8,7 @@ import {
   createMint,
   getAccount,
   getAssociatedTokenAddress,
+  getAssociatedTokenAddressSync,
   mintTo,
   createAssociatedTokenAccount,
 } from "@solana/spl-token";
@@ -18,6 +19,11 @@ import {
   SystemProgram,
   LAMPORTS_PER_SOL,
 } from "@solana/web3.js";
+import {
+  creatorProfilePda,
+  ensureCreatorProfile,
+  nextStreamPda,
+} from "./helpers/creator-profile";
 
 // Define account types for the program
 interface StreamAccount {
@@ -154,11 +160,13 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       // Assign to variables
       eval(`${account} = tokenAccount`);
     }
+
+    // create_stream takes its stream ID from the creator's profile
+    await ensureCreatorProfile(program, creator.publicKey);
   });
 
   describe("Basic Functionality", () => {
     it("Creates a stream with valid parameters", async () => {
-      const streamId = new BN(1);
       const title = "Test Stream";
       const startTime = new BN(Date.now() / 1000);
       const lockOffsetSecs = new BN(300);
@@ -166,18 +174,10 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       const precision = 2;
       const gracePeriodSecs = new BN(60);
 
-      [streamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          streamId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      streamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          streamId,
           title,
           startTime,
           lockOffsetSecs,
@@ -186,6 +186,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           gracePeriodSecs,
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: streamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -244,7 +245,6 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       try {
         await program.methods
           .createStream(
-            new BN(999),
             longTitle,
             new BN(Date.now() / 1000),
             new BN(300),
@@ -253,14 +253,8 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
             new BN(60),
           )
           .accounts({
-            stream: PublicKey.findProgramAddressSync(
-              [
-                Buffer.from("stream"),
-                creator.publicKey.toBuffer(),
-                new BN(999).toArrayLike(Buffer, "le", 8),
-              ],
-              program.programId,
-            )[0],
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+            stream: await nextStreamPda(program, creator.publicKey),
             creator: creator.publicKey,
             systemProgram: SystemProgram.programId,
           })
@@ -276,7 +270,6 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       try {
         await program.methods
           .createStream(
-            new BN(998),
             "Zero Tip Stream",
             new BN(Date.now() / 1000),
             new BN(300),
@@ -285,14 +278,8 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
             new BN(60),
           )
           .accounts({
-            stream: PublicKey.findProgramAddressSync(
-              [
-                Buffer.from("stream"),
-                creator.publicKey.toBuffer(),
-                new BN(998).toArrayLike(Buffer, "le", 8),
-              ],
-              program.programId,
-            )[0],
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+            stream: await nextStreamPda(program, creator.publicKey),
             creator: creator.publicKey,
             systemProgram: SystemProgram.programId,
           })
@@ -308,7 +295,6 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       try {
         await program.methods
           .createStream(
-            new BN(997),
             "High Tip Stream",
             new BN(Date.now() / 1000),
             new BN(300),
@@ -317,14 +303,8 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
             new BN(60),
           )
           .accounts({
-            stream: PublicKey.findProgramAddressSync(
-              [
-                Buffer.from("stream"),
-                creator.publicKey.toBuffer(),
-                new BN(997).toArrayLike(Buffer, "le", 8),
-              ],
-              program.programId,
-            )[0],
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+            stream: await nextStreamPda(program, creator.publicKey),
             creator: creator.publicKey,
             systemProgram: SystemProgram.programId,
           })
@@ -336,11 +316,10 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       }
     });
 
-    it("Rejects duplicate stream ID from same creator", async () => {
+    it("Rejects a stream PDA other than the profile's next ID", async () => {
       try {
         await program.methods
-          .createStream(
-            new BN(1), // Same ID as first stream
+          .createStream( // Placeholder, dropped below
             "Duplicate Stream",
             new BN(Date.now() / 1000),
             new BN(300),
@@ -349,32 +328,25 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
             new BN(60),
           )
           .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
             stream: streamPda, // Same PDA
             creator: creator.publicKey,
             systemProgram: SystemProgram.programId,
           })
           .rpc();
 
-        expect.fail("Should have failed with duplicate stream ID");
+        expect.fail("Should have failed with a seeds mismatch");
       } catch (error) {
-        expect((error as any).toString()).to.include("already in use");
+        expect((error as any).toString()).to.include("ConstraintSeeds");
       }
     });
 
     it("Creates stream with maximum valid title length (200 chars)", async () => {
       const maxTitle = "A".repeat(200);
-      const [maxStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(996).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const maxStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(996),
           maxTitle,
           new BN(Date.now() / 1000),
           new BN(300),
@@ -383,6 +355,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: maxStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -399,19 +372,11 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
 
   describe("Edge Cases - Stream Activation", () => {
     it("Prevents activation by non-creator", async () => {
-      const [nonCreatorStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(995).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const nonCreatorStreamPda = await nextStreamPda(program, creator.publicKey);
 
       // Create stream first
       await program.methods
         .createStream(
-          new BN(995),
           "Non-creator Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -420,6 +385,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: nonCreatorStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -470,19 +436,11 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
     });
 
     it("Prevents activation after stream is resolved", async () => {
-      const [resolvedStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(994).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const resolvedStreamPda = await nextStreamPda(program, creator.publicKey);
 
       // Create and set up stream
       await program.methods
         .createStream(
-          new BN(994),
           "Resolved Stream Test",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -491,26 +449,36 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: resolvedStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
 
-      // End and resolve stream
+      // Lock, end and resolve stream
+      await program.methods
+        .lockStream()
+        .accounts({
+          stream: resolvedStreamPda,
+          authority: creator.publicKey,
+        })
+        .rpc();
+
       await program.methods
         .endStream()
         .accounts({
           stream: resolvedStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
         })
         .rpc();
 
       await program.methods
         .resolvePrediction(1)
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: resolvedStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
           vault: vaultPda,
           creatorTokenAccount: creatorTokenAccount,
           vaultTokenAccount: vaultTokenAccount,
@@ -542,18 +510,10 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
 
     before(async () => {
       // Create stream for prediction tests
-      [predictionStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(100).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      predictionStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(100),
           "Prediction Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -562,6 +522,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: predictionStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -776,18 +737,10 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
 
     before(async () => {
       // Create stream for resolution tests
-      [resolutionStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(200).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      resolutionStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(200),
           "Resolution Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -796,6 +749,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: resolutionStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -843,7 +797,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           .endStream()
           .accounts({
             stream: resolutionStreamPda,
-            creator: maliciousActor.publicKey, // Not the creator
+            authority: maliciousActor.publicKey, // Not the creator
           })
           .signers([maliciousActor])
           .rpc();
@@ -854,13 +808,14 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       }
     });
 
-    it("Prevents resolution before stream is ended", async () => {
+    it("Prevents resolution before stream is locked", async () => {
       try {
         await program.methods
           .resolvePrediction(1)
           .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
             stream: resolutionStreamPda,
-            creator: creator.publicKey,
+            authority: creator.publicKey,
             vault: resolutionVaultPda,
             creatorTokenAccount: creatorTokenAccount,
             vaultTokenAccount: resolutionVaultTokenAccount,
@@ -868,19 +823,153 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           })
           .rpc();
 
-        expect.fail("Should have failed with stream not ended");
+        expect.fail("Should have failed with stream not locked");
+      } catch (error) {
+        expect((error as any).toString()).to.include("StreamNotLocked");
+      }
+    });
+
+    const setUpLockedStream = async (minObservationSecs: number) => {
+      const streamPda = await nextStreamPda(program, creator.publicKey);
+      const [vaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), streamPda.toBuffer()],
+        program.programId,
+      );
+      const vaultTokenAccount = await getAssociatedTokenAddress(
+        tokenMint,
+        vaultPda,
+        true,
+      );
+
+      await program.methods
+        .createStream(
+          "Observation Window Stream",
+          new BN(Date.now() / 1000),
+          new BN(300),
+          1000,
+          2,
+          new BN(60),
+        )
+        .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+          stream: streamPda,
+          creator: creator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      if (minObservationSecs > 0) {
+        await program.methods
+          .configureObservationWindow(new BN(minObservationSecs))
+          .accounts({
+            stream: streamPda,
+            creator: creator.publicKey,
+          })
+          .rpc();
+      }
+
+      await program.methods
+        .activateStream()
+        .accounts({
+          stream: streamPda,
+          creator: creator.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .initializeTokenVault()
+        .accounts({
+          creator: creator.publicKey,
+          stream: streamPda,
+          vault: vaultPda,
+          tokenMint: tokenMint,
+          vaultTokenAccount: vaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .lockStream()
+        .accounts({
+          stream: streamPda,
+          authority: creator.publicKey,
+        })
+        .rpc();
+
+      return { streamPda, vaultPda, vaultTokenAccount };
+    };
+
+    it("Prevents resolution inside the observation window", async () => {
+      const { streamPda, vaultPda, vaultTokenAccount } =
+        await setUpLockedStream(3600);
+
+      try {
+        await program.methods
+          .resolvePrediction(1)
+          .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+            stream: streamPda,
+            authority: creator.publicKey,
+            vault: vaultPda,
+            creatorTokenAccount: creatorTokenAccount,
+            vaultTokenAccount: vaultTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+
+        expect.fail("Should have failed with observation window open");
       } catch (error) {
-        expect((error as any).toString()).to.include("Stream not ended");
+        expect((error as any).toString()).to.include("ObservationWindowOpen");
       }
+
+      const streamAccount = await program.account.stream.fetch(streamPda);
+      expect(streamAccount.isResolved).to.be.false;
+      expect(streamAccount.isActive).to.be.true;
+    });
+
+    it("Ends a locked stream when it is resolved without end_stream", async () => {
+      const { streamPda, vaultPda, vaultTokenAccount } =
+        await setUpLockedStream(0);
+
+      await program.methods
+        .resolvePrediction(1)
+        .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+          stream: streamPda,
+          authority: creator.publicKey,
+          vault: vaultPda,
+          creatorTokenAccount: creatorTokenAccount,
+          vaultTokenAccount: vaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const streamAccount = await program.account.stream.fetch(streamPda);
+      expect(streamAccount.isResolved).to.be.true;
+      expect(streamAccount.isActive).to.be.false;
+      expect(streamAccount.endTime.toNumber()).to.be.greaterThan(0);
+      expect(streamAccount.endTime.toNumber()).to.be.at.least(
+        streamAccount.lockedAt.toNumber(),
+      );
     });
 
     it("Prevents double resolution", async () => {
-      // End stream
+      // Lock and end stream
+      await program.methods
+        .lockStream()
+        .accounts({
+          stream: resolutionStreamPda,
+          authority: creator.publicKey,
+        })
+        .rpc();
+
       await program.methods
         .endStream()
         .accounts({
           stream: resolutionStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
         })
         .rpc();
 
@@ -888,8 +977,9 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       await program.methods
         .resolvePrediction(1)
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: resolutionStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
           vault: resolutionVaultPda,
           creatorTokenAccount: creatorTokenAccount,
           vaultTokenAccount: resolutionVaultTokenAccount,
@@ -902,8 +992,9 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
         await program.methods
           .resolvePrediction(2)
           .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
             stream: resolutionStreamPda,
-            creator: creator.publicKey,
+            authority: creator.publicKey,
             vault: resolutionVaultPda,
             creatorTokenAccount: creatorTokenAccount,
             vaultTokenAccount: resolutionVaultTokenAccount,
@@ -918,19 +1009,11 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
     });
 
     it("Handles no-winners scenario gracefully", async () => {
-      const [noWinnerStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(201).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const noWinnerStreamPda = await nextStreamPda(program, creator.publicKey);
 
       // Create stream
       await program.methods
         .createStream(
-          new BN(201),
           "No Winner Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -939,6 +1022,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: noWinnerStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -980,11 +1064,19 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
         .rpc();
 
       // No one submits predictions - end and resolve
+      await program.methods
+        .lockStream()
+        .accounts({
+          stream: noWinnerStreamPda,
+          authority: creator.publicKey,
+        })
+        .rpc();
+
       await program.methods
         .endStream()
         .accounts({
           stream: noWinnerStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
         })
         .rpc();
 
@@ -992,8 +1084,9 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       await program.methods
         .resolvePrediction(1)
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: noWinnerStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
           vault: noWinnerVaultPda,
           creatorTokenAccount: creatorTokenAccount,
           vaultTokenAccount: noWinnerVaultTokenAccount,
@@ -1018,18 +1111,10 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
 
     before(async () => {
       // Create stream for claim tests
-      [claimStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(300).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      claimStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(300),
           "Claim Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -1038,6 +1123,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: claimStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -1129,20 +1215,29 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
         .signers([viewer2])
         .rpc();
 
-      // End and resolve with choice 1 (viewer wins)
+      // Lock, end and resolve with choice 1 (viewer wins)
+      await program.methods
+        .lockStream()
+        .accounts({
+          stream: claimStreamPda,
+          authority: creator.publicKey,
+        })
+        .rpc();
+
       await program.methods
         .endStream()
         .accounts({
           stream: claimStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
         })
         .rpc();
 
       await program.methods
         .resolvePrediction(1)
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: claimStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
           vault: claimVaultPda,
           creatorTokenAccount: creatorTokenAccount,
           vaultTokenAccount: claimVaultTokenAccount,
@@ -1262,21 +1357,14 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
     let cancelVaultPda: PublicKey;
     let cancelVaultTokenAccount: PublicKey;
     let cancelPredictionPda: PublicKey;
+    let refundStreamPda: PublicKey;
 
     before(async () => {
       // Create stream for cancellation tests
-      [cancelStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(400).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      cancelStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(400),
           "Cancellation Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -1285,6 +1373,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: cancelStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -1356,6 +1445,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
         await program.methods
           .cancelStream()
           .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
             stream: cancelStreamPda,
             creator: maliciousActor.publicKey,
           })
@@ -1371,20 +1461,29 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
     });
 
     it("Prevents cancellation after stream is resolved", async () => {
-      // End and resolve stream
+      // Lock, end and resolve stream
+      await program.methods
+        .lockStream()
+        .accounts({
+          stream: cancelStreamPda,
+          authority: creator.publicKey,
+        })
+        .rpc();
+
       await program.methods
         .endStream()
         .accounts({
           stream: cancelStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
         })
         .rpc();
 
       await program.methods
         .resolvePrediction(1)
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: cancelStreamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
           vault: cancelVaultPda,
           creatorTokenAccount: creatorTokenAccount,
           vaultTokenAccount: cancelVaultTokenAccount,
@@ -1397,6 +1496,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
         await program.methods
           .cancelStream()
           .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
             stream: cancelStreamPda,
             creator: creator.publicKey,
           })
@@ -1410,18 +1510,10 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
 
     it("Allows full refund after cancellation", async () => {
       // Create new stream for this test
-      const [refundStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(401).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      refundStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(401),
           "Refund Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -1430,6 +1522,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: refundStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -1503,6 +1596,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
       await program.methods
         .cancelStream()
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: refundStreamPda,
           creator: creator.publicKey,
         })
@@ -1535,16 +1629,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
     });
 
     it("Prevents double refund claim", async () => {
-      // Declare variables for this test
-      const [refundStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(402).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
-
+      // Reuse the stream refunded by the previous test
       const [refundVaultPda] = PublicKey.findProgramAddressSync(
         [Buffer.from("vault"), refundStreamPda.toBuffer()],
         program.programId,
@@ -1588,21 +1673,514 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
     });
   });
 
-  describe("Security Edge Cases", () => {
-    it("Prevents unauthorized token vault initialization", async () => {
-      const [secureStreamPda] = PublicKey.findProgramAddressSync(
+  describe("Edge Cases - Stake Limits", () => {
+    const createScalarStream = async () => {
+      const scalarStreamPda = await nextStreamPda(program, creator.publicKey);
+
+      await program.methods
+        .createStream(
+          "Scalar Stake Limit Stream",
+          new BN(Date.now() / 1000),
+          new BN(300),
+          1000,
+          2,
+          new BN(60),
+        )
+        .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+          stream: scalarStreamPda,
+          creator: creator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .configureScalar(new BN(0), new BN(100), { inverseDistance: {} })
+        .accounts({
+          stream: scalarStreamPda,
+          creator: creator.publicKey,
+        })
+        .rpc();
+
+      return scalarStreamPda;
+    };
+
+    it("Rejects a share cap on an unseedable pool without a depth floor", async () => {
+      const scalarStreamPda = await createScalarStream();
+
+      try {
+        await program.methods
+          .configureStakeLimits(new BN(0), new BN(0), 2500, new BN(0))
+          .accounts({
+            stream: scalarStreamPda,
+            creator: creator.publicKey,
+          })
+          .rpc();
+
+        expect.fail("Should have failed with invalid config");
+      } catch (error) {
+        expect((error as any).toString()).to.include("InvalidConfig");
+      }
+    });
+
+    it("Accepts the first stake into an empty share-capped scalar pool", async () => {
+      const scalarStreamPda = await createScalarStream();
+      const floor = 1_000 * 1_000_000;
+
+      await program.methods
+        .configureStakeLimits(new BN(0), new BN(0), 2500, new BN(floor))
+        .accounts({
+          stream: scalarStreamPda,
+          creator: creator.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .activateStream()
+        .accounts({
+          stream: scalarStreamPda,
+          creator: creator.publicKey,
+        })
+        .rpc();
+
+      const [scalarVaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), scalarStreamPda.toBuffer()],
+        program.programId,
+      );
+      const scalarVaultTokenAccount = await getAssociatedTokenAddress(
+        tokenMint,
+        scalarVaultPda,
+        true,
+      );
+
+      await program.methods
+        .initializeTokenVault()
+        .accounts({
+          creator: creator.publicKey,
+          stream: scalarStreamPda,
+          vault: scalarVaultPda,
+          tokenMint: tokenMint,
+          vaultTokenAccount: scalarVaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const [participantPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("participant"),
+          scalarStreamPda.toBuffer(),
+          viewer.publicKey.toBuffer(),
+        ],
+        program.programId,
+      );
+
+      await program.methods
+        .joinStream(new BN(0), null)
+        .accounts({
+          stream: scalarStreamPda,
+          participant: participantPda,
+          viewer: viewer.publicKey,
+        })
+        .signers([viewer])
+        .rpc();
+
+      const [predictionPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("prediction"),
+          scalarStreamPda.toBuffer(),
+          viewer.publicKey.toBuffer(),
+        ],
+        program.programId,
+      );
+
+      await program.methods
+        .submitScalarPrediction(new BN(42), new BN(10 * 1_000_000))
+        .accounts({
+          stream: scalarStreamPda,
+          prediction: predictionPda,
+          vault: scalarVaultPda,
+          viewerTokenAccount: viewerTokenAccount,
+          vaultTokenAccount: scalarVaultTokenAccount,
+          participant: participantPda,
+          viewer: viewer.publicKey,
+        })
+        .signers([viewer])
+        .rpc();
+
+      const streamAccount = await program.account.stream.fetch(scalarStreamPda);
+      expect(streamAccount.totalStake.toNumber()).to.equal(10 * 1_000_000);
+    });
+  });
+
+  describe("Edge Cases - Stream Gate", () => {
+    let gateMint: PublicKey;
+    let gateTokenAccount: PublicKey;
+    let challengeStreamPda: PublicKey;
+
+    const streamAccounts = (streamPda: PublicKey) => {
+      const [vault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), streamPda.toBuffer()],
+        program.programId,
+      );
+      return {
+        vault,
+        vaultTokenAccount: getAssociatedTokenAddressSync(tokenMint, vault, true),
+      };
+    };
+
+    // Create a stream, let `configure` pick its market mode, optionally gate it on
+    // holding the gate token, then activate it and open its vault
+    const createStream = async (
+      gated: boolean,
+      configure?: (streamPda: PublicKey) => Promise<void>,
+    ) => {
+      const gatedStreamPda = await nextStreamPda(program, creator.publicKey);
+
+      await program.methods
+        .createStream(
+          "Gated Stream",
+          new BN(Date.now() / 1000),
+          new BN(300),
+          1000,
+          2,
+          new BN(60),
+        )
+        .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+          stream: gatedStreamPda,
+          creator: creator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      if (configure) {
+        await configure(gatedStreamPda);
+      }
+
+      if (gated) {
+        await program.methods
+          .setStreamGate({
+            tokenBalance: { mint: gateMint, minAmount: new BN(1) },
+          })
+          .accounts({
+            stream: gatedStreamPda,
+            creator: creator.publicKey,
+          })
+          .rpc();
+      }
+
+      await program.methods
+        .activateStream()
+        .accounts({
+          stream: gatedStreamPda,
+          creator: creator.publicKey,
+        })
+        .rpc();
+
+      const { vault, vaultTokenAccount } = streamAccounts(gatedStreamPda);
+      await program.methods
+        .initializeTokenVault()
+        .accounts({
+          creator: creator.publicKey,
+          stream: gatedStreamPda,
+          vault,
+          tokenMint: tokenMint,
+          vaultTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      return gatedStreamPda;
+    };
+
+    const expectGateNotSatisfied = async (attempt: Promise<unknown>) => {
+      try {
+        await attempt;
+        expect.fail("Should have failed with gate not satisfied");
+      } catch (error) {
+        expect((error as any).toString()).to.include("GateNotSatisfied");
+      }
+    };
+
+    const challengePda = (streamPda: PublicKey, owner: PublicKey, id: number) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("challenge"),
+          streamPda.toBuffer(),
+          owner.toBuffer(),
+          new BN(id).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId,
+      )[0];
+
+    before(async () => {
+      // Only `viewer` holds the gate token; `viewer2` never joins
+      gateMint = await createMint(
+        provider.connection,
+        creator.payer,
+        creator.publicKey,
+        null,
+        0,
+      );
+      gateTokenAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        creator.payer,
+        gateMint,
+        viewer.publicKey,
+      );
+      await mintTo(
+        provider.connection,
+        creator.payer,
+        gateMint,
+        gateTokenAccount,
+        creator.publicKey,
+        1,
+      );
+
+      challengeStreamPda = await createStream(true);
+    });
+
+    it("Rejects LMSR buys on a gated stream without a participant", async () => {
+      const lmsrStreamPda = await createStream(true, (streamPda) =>
+        program.methods
+          .configureLmsr(new BN(1_000_000), 2)
+          .accounts({ stream: streamPda, creator: creator.publicKey })
+          .rpc(),
+      );
+      const { vault, vaultTokenAccount } = streamAccounts(lmsrStreamPda);
+      const [positionPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("lmsr_position"),
+          lmsrStreamPda.toBuffer(),
+          viewer2.publicKey.toBuffer(),
+        ],
+        program.programId,
+      );
+
+      await program.methods
+        .openLmsrPosition()
+        .accounts({
+          stream: lmsrStreamPda,
+          position: positionPda,
+          viewer: viewer2.publicKey,
+        })
+        .signers([viewer2])
+        .rpc();
+
+      await expectGateNotSatisfied(
+        program.methods
+          .buyShares(0, new BN(1_000), new BN(1_000_000_000))
+          .accounts({
+            stream: lmsrStreamPda,
+            position: positionPda,
+            vault,
+            viewerTokenAccount: viewer2TokenAccount,
+            vaultTokenAccount,
+            viewer: viewer2.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([viewer2])
+          .rpc(),
+      );
+    });
+
+    it("Rejects tokenized predictions on a gated stream without a participant", async () => {
+      const tokenStreamPda = await createStream(true, (streamPda) =>
+        program.methods
+          .configureOutcomeTokens()
+          .accounts({ stream: streamPda, creator: creator.publicKey })
+          .rpc(),
+      );
+      const { vault, vaultTokenAccount } = streamAccounts(tokenStreamPda);
+      const [outcomeMint] = PublicKey.findProgramAddressSync(
+        [Buffer.from("outcome_mint"), tokenStreamPda.toBuffer(), Buffer.from([0])],
+        program.programId,
+      );
+
+      await program.methods
+        .createOutcomeMint(0)
+        .accounts({
+          stream: tokenStreamPda,
+          creator: creator.publicKey,
+          vault,
+          tokenMint,
+          outcomeMint,
+        })
+        .rpc();
+      const viewerOutcomeAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        creator.payer,
+        outcomeMint,
+        viewer2.publicKey,
+      );
+
+      await expectGateNotSatisfied(
+        program.methods
+          .submitTokenizedPrediction(0, new BN(1_000_000))
+          .accounts({
+            stream: tokenStreamPda,
+            outcomeMint,
+            viewerOutcomeAccount,
+            vault,
+            viewerTokenAccount: viewer2TokenAccount,
+            vaultTokenAccount,
+            viewer: viewer2.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([viewer2])
+          .rpc(),
+      );
+    });
+
+    it("Rejects opening a challenge on a gated stream without a participant", async () => {
+      const { vault, vaultTokenAccount } = streamAccounts(challengeStreamPda);
+
+      await expectGateNotSatisfied(
+        program.methods
+          .openChallenge(new BN(1), 0, new BN(1_000_000), null)
+          .accounts({
+            stream: challengeStreamPda,
+            challenge: challengePda(challengeStreamPda, viewer2.publicKey, 1),
+            vault,
+            challengerTokenAccount: viewer2TokenAccount,
+            vaultTokenAccount,
+            challenger: viewer2.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([viewer2])
+          .rpc(),
+      );
+    });
+
+    it("Rejects accepting a challenge on a gated stream without a participant", async () => {
+      const { vault, vaultTokenAccount } = streamAccounts(challengeStreamPda);
+      const [participantPda] = PublicKey.findProgramAddressSync(
         [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(500).toArrayLike(Buffer, "le", 8),
+          Buffer.from("participant"),
+          challengeStreamPda.toBuffer(),
+          viewer.publicKey.toBuffer(),
         ],
         program.programId,
       );
+      const challenge = challengePda(challengeStreamPda, viewer.publicKey, 1);
+
+      // The token holder joins and opens a challenge
+      await program.methods
+        .joinStream(new BN(0), null)
+        .accounts({
+          stream: challengeStreamPda,
+          participant: participantPda,
+          gateTokenAccount,
+          viewer: viewer.publicKey,
+        })
+        .signers([viewer])
+        .rpc();
+
+      await program.methods
+        .openChallenge(new BN(1), 0, new BN(1_000_000), null)
+        .accounts({
+          stream: challengeStreamPda,
+          challenge,
+          vault,
+          challengerTokenAccount: viewerTokenAccount,
+          vaultTokenAccount,
+          participant: participantPda,
+          challenger: viewer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([viewer])
+        .rpc();
+
+      await expectGateNotSatisfied(
+        program.methods
+          .acceptChallenge()
+          .accounts({
+            stream: challengeStreamPda,
+            challenge,
+            vault,
+            acceptorTokenAccount: viewer2TokenAccount,
+            vaultTokenAccount,
+            acceptor: viewer2.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([viewer2])
+          .rpc(),
+      );
+    });
+
+    it("Rejects parlays with a gated leg without the bettor's participant", async () => {
+      const homeStreamPda = await createStream(false);
+      const { vault, vaultTokenAccount } = streamAccounts(homeStreamPda);
+      const [parlayPool] = PublicKey.findProgramAddressSync(
+        [Buffer.from("parlay_pool"), homeStreamPda.toBuffer()],
+        program.programId,
+      );
+      const [parlay] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("parlay"),
+          parlayPool.toBuffer(),
+          viewer2.publicKey.toBuffer(),
+          new BN(1).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId,
+      );
+
+      await program.methods
+        .initializeParlayPool()
+        .accounts({
+          stream: homeStreamPda,
+          parlayPool,
+          creator: creator.publicKey,
+        })
+        .rpc();
+
+      await expectGateNotSatisfied(
+        program.methods
+          .placeParlay(
+            new BN(1),
+            [
+              { stream: homeStreamPda, choice: 0 },
+              { stream: challengeStreamPda, choice: 1 },
+            ],
+            new BN(1_000_000),
+          )
+          .accounts({
+            stream: homeStreamPda,
+            parlayPool,
+            parlay,
+            vault,
+            bettorTokenAccount: viewer2TokenAccount,
+            vaultTokenAccount,
+            bettor: viewer2.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(
+            [homeStreamPda, challengeStreamPda].map((pubkey) => ({
+              pubkey,
+              isWritable: false,
+              isSigner: false,
+            })),
+          )
+          .signers([viewer2])
+          .rpc(),
+      );
+    });
+  });
+
+  describe("Security Edge Cases", () => {
+    it("Prevents unauthorized token vault initialization", async () => {
+      const secureStreamPda = await nextStreamPda(program, creator.publicKey);
 
       // Create stream
       await program.methods
         .createStream(
-          new BN(500),
           "Security Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -1611,6 +2189,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: secureStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -1654,19 +2233,11 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
     });
 
     it("Handles maximum number of predictions per stream", async () => {
-      const [maxPredictionsStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(501).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const maxPredictionsStreamPda = await nextStreamPda(program, creator.publicKey);
 
       // Create stream
       await program.methods
         .createStream(
-          new BN(501),
           "Max Predictions Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -1675,6 +2246,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: maxPredictionsStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -1751,21 +2323,13 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
 
     it("Prevents prediction submission after lock time", async () => {
       // Create stream with very short lock time
-      const [lockedStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(502).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const lockedStreamPda = await nextStreamPda(program, creator.publicKey);
 
       const pastStartTime = new BN(Date.now() / 1000 - 10); // 10 seconds ago
       const shortLockOffset = new BN(1); // 1 second lock
 
       await program.methods
         .createStream(
-          new BN(502),
           "Quick Lock Stream",
           pastStartTime,
           shortLockOffset,
@@ -1774,6 +2338,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: lockedStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -1851,19 +2416,11 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
 
   describe("Performance Edge Cases", () => {
     it("Handles concurrent prediction submissions", async () => {
-      const [concurrentStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(600).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const concurrentStreamPda = await nextStreamPda(program, creator.publicKey);
 
       // Create stream
       await program.methods
         .createStream(
-          new BN(600),
           "Concurrent Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -1872,6 +2429,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: concurrentStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -1971,19 +2529,11 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
     });
 
     it("Handles maximum stake amounts", async () => {
-      const [maxStakeStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(601).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const maxStakeStreamPda = await nextStreamPda(program, creator.publicKey);
 
       // Create stream
       await program.methods
         .createStream(
-          new BN(601),
           "Max Stake Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -1992,6 +2542,7 @@ describe("CypherCast - Comprehensive Test Suite with Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: maxStakeStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -17,6 +17,12 @@ import {
   SystemProgram,
   LAMPORTS_PER_SOL,
 } from "@solana/web3.js";
+import {
+  creatorProfilePda,
+  ensureCreatorProfile,
+  nextStreamId,
+  nextStreamPda,
+} from "../helpers/creator-profile";
 
 describe("CypherCast - Boundary Conditions Edge Cases", () => {
   const provider = anchor.AnchorProvider.env();
@@ -66,30 +72,25 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
       creator.publicKey,
       10000 * 1_000_000, // 10000 tokens for boundary testing
     );
+
+    // create_stream takes its stream ID from the creator's profile
+    await ensureCreatorProfile(program, creator.publicKey);
   });
 
   describe("Numeric Boundary Testing", () => {
     it("Creates stream with minimum valid values", async () => {
-      const streamId = new BN(0); // Minimum stream ID
+      const streamId = await nextStreamId(program, creator.publicKey);
       const title = "Min"; // Minimum title length
-      const startTime = new BN(1); // Minimum start time
-      const lockOffsetSecs = new BN(0); // Minimum lock offset
+      const startTime = new BN(Math.floor(Date.now() / 1000)); // Now
+      const lockOffsetSecs = new BN(60); // Short lock offset
       const tipBps = 1; // Minimum tip (0.01%)
       const precision = 0; // Minimum precision
       const gracePeriodSecs = new BN(0); // Minimum grace period
 
-      const [minStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          streamId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const minStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          streamId,
           title,
           startTime,
           lockOffsetSecs,
@@ -98,6 +99,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           gracePeriodSecs,
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: minStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -112,26 +114,18 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
     });
 
     it("Creates stream with maximum valid values", async () => {
-      const streamId = new BN("18446744073709551615"); // Maximum u64
+      const streamId = await nextStreamId(program, creator.publicKey);
       const title = "A".repeat(200); // Maximum title length
-      const startTime = new BN("9223372036854775807"); // Maximum i64
-      const lockOffsetSecs = new BN("9223372036854775807"); // Maximum i64
+      const startTime = new BN(Math.floor(Date.now() / 1000)); // Now
+      const lockOffsetSecs = new BN(86400 * 365); // MAX_LOCK_OFFSET_SECS (1 year)
       const tipBps = 10000; // Maximum tip (100%)
       const precision = 9; // Maximum precision per Phase 2.5 docs
-      const gracePeriodSecs = new BN("9223372036854775807"); // Maximum i64
+      const gracePeriodSecs = new BN(86400 * 7); // MAX_GRACE_PERIOD_SECS (7 days)
 
-      const [maxStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          streamId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const maxStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          streamId,
           title,
           startTime,
           lockOffsetSecs,
@@ -140,6 +134,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           gracePeriodSecs,
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: maxStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -153,55 +148,12 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
       expect(streamAccount.precision).to.equal(precision);
     });
 
-    it("Rejects stream ID that exceeds u64 maximum", async () => {
-      try {
-        const invalidStreamId = new BN("18446744073709551616"); // u64 + 1
-        const [invalidStreamPda] = PublicKey.findProgramAddressSync(
-          [
-            Buffer.from("stream"),
-            creator.publicKey.toBuffer(),
-            invalidStreamId.toArrayLike(Buffer, "le", 8),
-          ],
-          program.programId,
-        );
-
-        await program.methods
-          .createStream(
-            invalidStreamId,
-            "Invalid Stream",
-            new BN(Date.now() / 1000),
-            new BN(300),
-            1000,
-            2,
-            new BN(60),
-          )
-          .accounts({
-            stream: invalidStreamPda,
-            creator: creator.publicKey,
-            systemProgram: SystemProgram.programId,
-          })
-          .rpc();
-
-        expect.fail("Should have failed with invalid stream ID");
-      } catch (error) {
-        expect(error.toString()).to.include("byte array longer");
-      }
-    });
-
     it("Rejects negative tip percentage", async () => {
       try {
-        const [negativeTipStreamPda] = PublicKey.findProgramAddressSync(
-          [
-            Buffer.from("stream"),
-            creator.publicKey.toBuffer(),
-            new BN(1001).toArrayLike(Buffer, "le", 8),
-          ],
-          program.programId,
-        );
+        const negativeTipStreamPda = await nextStreamPda(program, creator.publicKey);
 
         await program.methods
           .createStream(
-            new BN(1001),
             "Negative Tip Stream",
             new BN(Date.now() / 1000),
             new BN(300),
@@ -210,6 +162,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
             new BN(60),
           )
           .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
             stream: negativeTipStreamPda,
             creator: creator.publicKey,
             systemProgram: SystemProgram.programId,
@@ -225,18 +178,10 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
 
     it("Rejects precision value exceeding Phase 2.5 maximum", async () => {
       try {
-        const [invalidPrecisionStreamPda] = PublicKey.findProgramAddressSync(
-          [
-            Buffer.from("stream"),
-            creator.publicKey.toBuffer(),
-            new BN(1002).toArrayLike(Buffer, "le", 8),
-          ],
-          program.programId,
-        );
+        const invalidPrecisionStreamPda = await nextStreamPda(program, creator.publicKey);
 
         await program.methods
           .createStream(
-            new BN(1002),
             "Invalid Precision Stream",
             new BN(Date.now() / 1000),
             new BN(300),
@@ -245,6 +190,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
             new BN(60),
           )
           .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
             stream: invalidPrecisionStreamPda,
             creator: creator.publicKey,
             systemProgram: SystemProgram.programId,
@@ -265,18 +211,10 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
 
     before(async () => {
       // Setup stream for choice boundary testing
-      [choiceTestStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(2000).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      choiceTestStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(2000),
           "Choice Boundary Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -285,6 +223,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: choiceTestStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -482,18 +421,10 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
 
     before(async () => {
       // Setup stream for stake boundary testing
-      [stakeTestStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(2001).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      stakeTestStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(2001),
           "Stake Boundary Test Stream",
           new BN(Date.now() / 1000),
           new BN(300),
@@ -502,6 +433,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: stakeTestStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -718,53 +650,40 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
   });
 
   describe("Time Boundary Testing", () => {
-    it("Handles stream start time in the past", async () => {
+    it("Rejects stream start time in the past", async () => {
       const pastTime = new BN(Date.now() / 1000 - 3600); // 1 hour ago
-      const [pastTimeStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(3000).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const pastTimeStreamPda = await nextStreamPda(program, creator.publicKey);
 
-      await program.methods
-        .createStream(
-          new BN(3000),
-          "Past Time Stream",
-          pastTime,
-          new BN(300),
-          1000,
-          2,
-          new BN(60),
-        )
-        .accounts({
-          stream: pastTimeStreamPda,
-          creator: creator.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .rpc();
+      try {
+        await program.methods
+          .createStream(
+            "Past Time Stream",
+            pastTime,
+            new BN(300),
+            1000,
+            2,
+            new BN(60),
+          )
+          .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+            stream: pastTimeStreamPda,
+            creator: creator.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
 
-      const streamAccount =
-        await program.account.stream.fetch(pastTimeStreamPda);
-      expect(streamAccount.startTime.toString()).to.equal(pastTime.toString());
+        expect.fail("Should have failed with StartTimeInPast");
+      } catch (error) {
+        expect(error.toString()).to.include("StartTimeInPast");
+      }
     });
 
     it("Handles stream start time in the distant future", async () => {
       const futureTime = new BN(Date.now() / 1000 + 31536000); // 1 year from now
-      const [futureTimeStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(3001).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const futureTimeStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(3001),
           "Future Time Stream",
           futureTime,
           new BN(300),
@@ -773,6 +692,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: futureTimeStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -786,51 +706,66 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
       );
     });
 
-    it("Handles minimum lock time (0 seconds)", async () => {
-      const [noLockStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(3002).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+    it("Rejects a zero lock offset", async () => {
+      const noLockStreamPda = await nextStreamPda(program, creator.publicKey);
 
-      await program.methods
-        .createStream(
-          new BN(3002),
-          "No Lock Stream",
-          new BN(Date.now() / 1000),
-          new BN(0), // No lock time
-          1000,
-          2,
-          new BN(60),
-        )
-        .accounts({
-          stream: noLockStreamPda,
-          creator: creator.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .rpc();
+      try {
+        await program.methods
+          .createStream(
+            "No Lock Stream",
+            new BN(Date.now() / 1000),
+            new BN(0), // Locked from birth
+            1000,
+            2,
+            new BN(60),
+          )
+          .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+            stream: noLockStreamPda,
+            creator: creator.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+        expect.fail("Should have failed with InvalidLockOffset");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidLockOffset");
+      }
+    });
+
+    it("Rejects a negative grace period", async () => {
+      const negativeGraceStreamPda = await nextStreamPda(program, creator.publicKey);
+
+      try {
+        await program.methods
+          .createStream(
+            "Negative Grace Stream",
+            new BN(Date.now() / 1000),
+            new BN(300),
+            1000,
+            2,
+            new BN(-1),
+          )
+          .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
+            stream: negativeGraceStreamPda,
+            creator: creator.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
 
-      const streamAccount = await program.account.stream.fetch(noLockStreamPda);
-      expect(streamAccount.lockOffsetSecs.toString()).to.equal("0");
+        expect.fail("Should have failed with InvalidGracePeriod");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidGracePeriod");
+      }
     });
 
     it("Handles maximum reasonable lock time", async () => {
       const maxLockTime = new BN(86400 * 365); // 1 year in seconds
-      const [maxLockStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(3003).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const maxLockStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(3003),
           "Max Lock Stream",
           new BN(Date.now() / 1000),
           maxLockTime,
@@ -839,6 +774,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: maxLockStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -856,18 +792,10 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
   describe("String Boundary Testing", () => {
     it("Handles empty string gracefully", async () => {
       // Empty strings are actually allowed by the program
-      const [emptyTitleStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(4000).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const emptyTitleStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(4000),
           "", // Empty title - this should succeed
           new BN(Date.now() / 1000),
           new BN(300),
@@ -876,6 +804,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: emptyTitleStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -890,18 +819,10 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
     });
 
     it("Handles single character title", async () => {
-      const [singleCharStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(4001).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const singleCharStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(4001),
           "A", // Single character
           new BN(Date.now() / 1000),
           new BN(300),
@@ -910,6 +831,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: singleCharStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -923,18 +845,10 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
 
     it("Handles title with special characters", async () => {
       const specialTitle = "Stream!@#$%^&*()_+-=[]{}|;':\",./<>?";
-      const [specialCharStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(4002).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const specialCharStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(4002),
           specialTitle,
           new BN(Date.now() / 1000),
           new BN(300),
@@ -943,6 +857,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: specialCharStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -956,18 +871,10 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
 
     it("Handles title with Unicode characters", async () => {
       const unicodeTitle = "Stream 🎮🏆🎯 测试 🚀🌟";
-      const [unicodeStreamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          creator.publicKey.toBuffer(),
-          new BN(4003).toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      const unicodeStreamPda = await nextStreamPda(program, creator.publicKey);
 
       await program.methods
         .createStream(
-          new BN(4003),
           unicodeTitle,
           new BN(Date.now() / 1000),
           new BN(300),
@@ -976,6 +883,7 @@ describe("CypherCast - Boundary Conditions Edge Cases", () => {
           new BN(60),
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: unicodeStreamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -0,0 +1,63 @@
+import { Program } from "@coral-xyz/anchor";
+import BN from "bn.js";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+
+/** Profile PDA that allocates `creator`'s stream IDs and records their stats. */
+export function creatorProfilePda(programId: PublicKey, creator: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("creator_profile"), creator.toBuffer()],
+    programId,
+  )[0];
+}
+
+/** Stream PDA for `creator`'s stream `streamId`. */
+export function streamPdaFor(
+  programId: PublicKey,
+  creator: PublicKey,
+  streamId: BN,
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("stream"), creator.toBuffer(), streamId.toArrayLike(Buffer, "le", 8)],
+    programId,
+  )[0];
+}
+
+/**
+ * Create `creator`'s profile unless it exists. Test files share one validator, so the
+ * provider wallet's profile may already have been created by another file.
+ */
+export async function ensureCreatorProfile(
+  program: Program<any>,
+  creator: PublicKey,
+  signers: Keypair[] = [],
+): Promise<PublicKey> {
+  const profile = creatorProfilePda(program.programId, creator);
+  if ((await program.provider.connection.getAccountInfo(profile)) === null) {
+    await program.methods
+      .initializeCreatorProfile("")
+      .accounts({
+        creatorProfile: profile,
+        creator,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers(signers)
+      .rpc();
+  }
+  return profile;
+}
+
+/** ID the next `create_stream` by `creator` will allocate. */
+export async function nextStreamId(program: Program<any>, creator: PublicKey): Promise<BN> {
+  const profile = await (program.account as any).creatorProfile.fetch(
+    creatorProfilePda(program.programId, creator),
+  );
+  return profile.nextStreamId;
+}
+
+/** PDA the next `create_stream` by `creator` will initialize. */
+export async function nextStreamPda(
+  program: Program<any>,
+  creator: PublicKey,
+): Promise<PublicKey> {
+  return streamPdaFor(program.programId, creator, await nextStreamId(program, creator));
+}
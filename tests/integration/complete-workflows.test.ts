@@ -18,6 +18,11 @@ import {
   LAMPORTS_PER_SOL,
   SendTransactionError,
 } from "@solana/web3.js";
+import {
+  creatorProfilePda,
+  ensureCreatorProfile,
+  nextStreamPda,
+} from "../helpers/creator-profile";
 
 // Buffer import for CommonJS compatibility
 global.Buffer = global.Buffer || require("buffer").Buffer;
@@ -202,6 +207,9 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
       2000 * 1_000_000,
     );
     participantTokenAccounts.set(creator.publicKey, streamerTokenAccount);
+
+    // create_stream takes its stream ID from the creator's profile
+    await ensureCreatorProfile(program, creator.publicKey);
   });
 
   describe("Complete Stream Lifecycle - Single Winner", () => {
@@ -212,7 +220,6 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
 
     it("Complete workflow: Create -> Activate -> Predictions -> End -> Resolve -> Claim", async () => {
       // 1. Create Stream
-      const streamId = new BN(Date.now()); // Use timestamp for uniqueness
       const title = "Gaming Tournament Stream";
       const startTime = new BN(Date.now() / 1000);
       const lockOffsetSecs = new BN(600); // 10 minutes
@@ -220,18 +227,10 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
       const precision = 2;
       const gracePeriodSecs = new BN(300); // 5 minutes
 
-      [streamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          streamer.publicKey.toBuffer(),
-          streamId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      streamPda = await nextStreamPda(program, streamer.publicKey);
 
       await program.methods
         .createStream(
-          streamId,
           title,
           startTime,
           lockOffsetSecs,
@@ -240,6 +239,7 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
           gracePeriodSecs,
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
           stream: streamPda,
           creator: creator.publicKey,
           systemProgram: SystemProgram.programId,
@@ -372,12 +372,20 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
         expectedTotal.toString(),
       );
 
-      // 5. End Stream
+      // 5. Lock and end stream
+      await program.methods
+        .lockStream()
+        .accounts({
+          stream: streamPda,
+          authority: streamer.publicKey,
+        })
+        .rpc();
+
       await program.methods
         .endStream()
         .accounts({
           stream: streamPda,
-          creator: streamer.publicKey,
+          authority: streamer.publicKey,
         })
         .rpc();
 
@@ -400,8 +408,9 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
       await program.methods
         .resolvePrediction(winningChoice)
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, streamer.publicKey),
           stream: streamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
           vault: vaultPda,
           creatorTokenAccount: streamerTokenAccount,
           vaultTokenAccount: vaultTokenAccount,
@@ -491,7 +500,6 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
 
     it("Complete workflow with no winners", async () => {
       // 1. Create Stream
-      const streamId = new BN(Date.now() + 1000);
       const title = "No Winners Test Stream";
       const startTime = new BN(Date.now() / 1000);
       const lockOffsetSecs = new BN(300);
@@ -499,18 +507,10 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
       const precision = 2;
       const gracePeriodSecs = new BN(600);
 
-      [streamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          streamer.publicKey.toBuffer(),
-          streamId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      streamPda = await nextStreamPda(program, streamer.publicKey);
 
       await program.methods
         .createStream(
-          streamId,
           title,
           startTime,
           lockOffsetSecs,
@@ -519,6 +519,7 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
           gracePeriodSecs,
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, streamer.publicKey),
           stream: streamPda,
           creator: streamer.publicKey,
           systemProgram: SystemProgram.programId,
@@ -602,12 +603,20 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
           .rpc();
       }
 
-      // 4. End stream
+      // 4. Lock and end stream
+      await program.methods
+        .lockStream()
+        .accounts({
+          stream: streamPda,
+          authority: streamer.publicKey,
+        })
+        .rpc();
+
       await program.methods
         .endStream()
         .accounts({
           stream: streamPda,
-          creator: streamer.publicKey,
+          authority: streamer.publicKey,
         })
         .rpc();
 
@@ -623,8 +632,9 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
       await program.methods
         .resolvePrediction(3) // Choice 3 - no one predicted this
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, streamer.publicKey),
           stream: streamPda,
-          creator: creator.publicKey,
+          authority: creator.publicKey,
           vault: noWinnerVaultPda,
           creatorTokenAccount: streamerTokenAccount,
           vaultTokenAccount: noWinnerVaultTokenAccount,
@@ -693,7 +703,6 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
 
     it("Complete workflow with stream cancellation", async () => {
       // 1. Create Stream
-      const streamId = new BN(Date.now() + 2000);
       const title = "Cancellation Test Stream";
       const startTime = new BN(Date.now() / 1000);
       const lockOffsetSecs = new BN(300);
@@ -701,18 +710,10 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
       const precision = 2;
       const gracePeriodSecs = new BN(600);
 
-      [streamPda] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("stream"),
-          streamer.publicKey.toBuffer(),
-          streamId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId,
-      );
+      streamPda = await nextStreamPda(program, streamer.publicKey);
 
       await program.methods
         .createStream(
-          streamId,
           title,
           startTime,
           lockOffsetSecs,
@@ -721,6 +722,7 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
           gracePeriodSecs,
         )
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, streamer.publicKey),
           stream: streamPda,
           creator: streamer.publicKey,
           systemProgram: SystemProgram.programId,
@@ -807,6 +809,7 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
       await program.methods
         .cancelStream()
         .accounts({
+          creatorProfile: creatorProfilePda(program.programId, streamer.publicKey),
           stream: streamPda,
           creator: streamer.publicKey,
         })
@@ -900,20 +903,12 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
 
       // Create multiple streams
       for (const config of streamConfigs) {
-        const [streamPda] = PublicKey.findProgramAddressSync(
-          [
-            Buffer.from("stream"),
-            streamer.publicKey.toBuffer(),
-            config.id.toArrayLike(Buffer, "le", 8),
-          ],
-          program.programId,
-        );
+        const streamPda = await nextStreamPda(program, streamer.publicKey);
 
         streamPDAs.set(config.id, streamPda);
 
         await program.methods
           .createStream(
-            config.id,
             config.title,
             new BN(Date.now() / 1000),
             new BN(300),
@@ -922,6 +917,7 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
             new BN(600),
           )
           .accounts({
+            creatorProfile: creatorProfilePda(program.programId, creator.publicKey),
             stream: streamPda,
             creator: creator.publicKey,
             systemProgram: SystemProgram.programId,
@@ -1100,6 +1096,20 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
 
       await Promise.all(predictionPromises);
 
+      // Lock all streams so they can be resolved right away
+      const lockPromises = streamConfigs.map((config) => {
+        const streamPda = streamPDAs.get(config.id)!;
+        return program.methods
+          .lockStream()
+          .accounts({
+            stream: streamPda,
+            authority: creator.publicKey,
+          })
+          .rpc();
+      });
+
+      await Promise.all(lockPromises);
+
       // End all streams
       const endPromises = streamConfigs.map((config) => {
         const streamPda = streamPDAs.get(config.id)!;
@@ -1107,7 +1117,7 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
           .endStream()
           .accounts({
             stream: streamPda,
-            creator: creator.publicKey,
+            authority: creator.publicKey,
           })
           .rpc();
       });
@@ -1134,8 +1144,9 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
         return program.methods
           .resolvePrediction(config.winningChoice)
           .accounts({
+            creatorProfile: creatorProfilePda(program.programId, streamer.publicKey),
             stream: streamPda,
-            creator: creator.publicKey,
+            authority: creator.publicKey,
             vault: vaultPda,
             creatorTokenAccount: streamerTokenAccount,
             vaultTokenAccount: vaultTokenAccount,
@@ -1158,7 +1169,6 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
       describe("Edge Case Workflow - Maximum Load", () => {
         it("Handle maximum number of predictions in a single stream", async () => {
           // Create a stream for maximum load testing
-          const streamId = new BN(8888888); // Unique ID to avoid collisions
           const title = "Maximum Load Test Stream";
           const startTime = new BN(Date.now() / 1000);
           const lockOffsetSecs = new BN(300);
@@ -1166,18 +1176,10 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
           const precision = 2;
           const gracePeriodSecs = new BN(600);
 
-          const [maxLoadStreamPda] = PublicKey.findProgramAddressSync(
-            [
-              Buffer.from("stream"),
-              streamer.publicKey.toBuffer(),
-              streamId.toArrayLike(Buffer, "le", 8),
-            ],
-            program.programId,
-          );
+          const maxLoadStreamPda = await nextStreamPda(program, streamer.publicKey);
 
           await program.methods
             .createStream(
-              streamId,
               title,
               startTime,
               lockOffsetSecs,
@@ -1186,6 +1188,7 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
               gracePeriodSecs,
             )
             .accounts({
+              creatorProfile: creatorProfilePda(program.programId, streamer.publicKey),
               stream: maxLoadStreamPda,
               creator: streamer.publicKey,
               systemProgram: SystemProgram.programId,
@@ -1286,12 +1289,20 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
           // Should have exactly 5 successful predictions (one per viewer)
           expect(successfulPredictions.length).to.equal(5);
 
-          // End and resolve stream
+          // Lock, end and resolve stream
+          await program.methods
+            .lockStream()
+            .accounts({
+              stream: maxLoadStreamPda,
+              authority: streamer.publicKey,
+            })
+            .rpc();
+
           await program.methods
             .endStream()
             .accounts({
               stream: maxLoadStreamPda,
-              creator: streamer.publicKey,
+              authority: streamer.publicKey,
             })
             .rpc();
 
@@ -1306,8 +1317,9 @@ describe("CypherCast - Complete Workflow Integration Tests", () => {
           await program.methods
             .resolvePrediction(1)
             .accounts({
+              creatorProfile: creatorProfilePda(program.programId, streamer.publicKey),
               stream: maxLoadStreamPda,
-              creator: creator.publicKey,
+              authority: creator.publicKey,
               vault: maxLoadVaultPda,
               creatorTokenAccount: streamerTokenAccount,
               vaultTokenAccount: maxLoadVaultTokenAccount,